@@ -1,4 +1,45 @@
+use std::path::{Path, PathBuf};
+
+// Constants of the selected parameter set, used to generate the C header
+#[allow(dead_code)]
+#[path = "src/constants.rs"]
+mod constants;
+
 fn main() {
+    // The C sources are replaced by the Rust implementations in src/portable.rs on wasm32, which
+    // has no C toolchain and libc, and with the `portable` feature
+    println!("cargo::rustc-check-cfg=cfg(mayo_portable)");
+    let target_arch = std::env::var("CARGO_CFG_TARGET_ARCH").unwrap_or_default();
+    let portable = target_arch == "wasm32" || std::env::var("CARGO_FEATURE_PORTABLE").is_ok();
+    if portable {
+        println!("cargo:rustc-cfg=mayo_portable");
+    } else {
+        compile_c_sources();
+    }
+
+    // Header for the C interface in src/ffi.rs
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR").unwrap());
+    write_c_header(&out_dir.join("mayo_api.h"));
+
+    // Also place the header next to the cdylib/staticlib (target/<profile>/include)
+    if let Some(profile_dir) = out_dir.ancestors().nth(3) {
+        let include_dir = profile_dir.join("include");
+        if std::fs::create_dir_all(&include_dir).is_ok() {
+            write_c_header(&include_dir.join("mayo_api.h"));
+        }
+    }
+
+    // KAT generation through the C interface, used by tests/ffi_kat.rs. It needs the C randomness source.
+    if !portable {
+        cc::Build::new()
+            .file("src/genkat/PQCgenKAT_sign_ffi.c")
+            .include(&out_dir)
+            .include("src/genkat")
+            .compile("mayo_ffi_kat");
+    }
+}
+
+fn compile_c_sources() {
     let mut build = cc::Build::new();
 
     // Check if aes_neon is enabled
@@ -6,17 +47,70 @@ fn main() {
         println!("cargo:info=Using AES with NEON intrinsics");
         build.file("src/genkat/aes_arm.c");
         build.flag_if_supported("-march=armv8-a+crypto"); // Enable Cryptography extensions
-
     } else {
-
         println!("cargo:info=Using default AES");
         build.file("src/genkat/aes_c.c");
     }
 
-    build.file("src/genkat/randombytes_ctrdrbg.c")
+    build
+        .file("src/genkat/randombytes_ctrdrbg.c")
         .file("src/genkat/mem.c")
         .file("src/genkat/fips202.c")
         .file("src/arm_neon_intrinsic/armv8_intrinsic.c")
         .flag("-O3")
         .compile("randombytes_nist");
 }
+
+fn write_c_header(path: &Path) {
+    let version = constants::VERSION;
+    let prefix = format!("{}_", version);
+
+    let mut header = format!(
+        "/* C interface of MAYO ({version}). Generated by build.rs, do not edit. */
+
+#ifndef {version}_API_H
+#define {version}_API_H
+
+#include <stddef.h>
+
+#define {prefix}CRYPTO_SECRETKEYBYTES {sk_bytes}
+#define {prefix}CRYPTO_PUBLICKEYBYTES {pk_bytes}
+#define {prefix}CRYPTO_BYTES {sig_bytes}
+#define {prefix}CRYPTO_ALGNAME \"{version}\"
+
+/* All functions return 0 on success and -1 on failure */
+int {prefix}crypto_sign_keypair(unsigned char *pk, unsigned char *sk);
+int {prefix}crypto_sign(unsigned char *sm, unsigned long long *smlen,
+        const unsigned char *m, unsigned long long mlen, const unsigned char *sk);
+int {prefix}crypto_sign_open(unsigned char *m, unsigned long long *mlen,
+        const unsigned char *sm, unsigned long long smlen, const unsigned char *pk);
+int {prefix}crypto_sign_signature(unsigned char *sig, size_t *siglen,
+        const unsigned char *m, size_t mlen, const unsigned char *sk);
+int {prefix}crypto_sign_verify(const unsigned char *sig, size_t siglen,
+        const unsigned char *m, size_t mlen, const unsigned char *pk);
+
+/* NIST API names. Define MAYO_NO_NIST_NAMES to only get the prefixed names. */
+#ifndef MAYO_NO_NIST_NAMES
+",
+        sk_bytes = constants::CSK_BYTES,
+        pk_bytes = constants::CPK_BYTES,
+        sig_bytes = constants::SIG_BYTES,
+    );
+
+    for name in [
+        "CRYPTO_SECRETKEYBYTES",
+        "CRYPTO_PUBLICKEYBYTES",
+        "CRYPTO_BYTES",
+        "CRYPTO_ALGNAME",
+        "crypto_sign_keypair",
+        "crypto_sign",
+        "crypto_sign_open",
+        "crypto_sign_signature",
+        "crypto_sign_verify",
+    ] {
+        header.push_str(&format!("#define {name} {prefix}{name}\n"));
+    }
+    header.push_str("#endif\n\n#endif\n");
+
+    std::fs::write(path, header).expect("Failed to write the C header");
+}
@@ -0,0 +1,78 @@
+// Writes a seed corpus for the fuzz targets from the NIST KAT file of the compiled parameter set:
+//
+//   cargo run --example seed_corpus [--no-default-features --features mayoN]
+//
+// The inputs are valid keys, signatures and signed messages, so the fuzzer starts from the
+// accepting paths instead of having to find a valid signature itself.
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use lib::constants::{CSK_BYTES, SIG_BYTES, VERSION};
+use lib::kat::{kat_response_path, read_kat_file};
+use lib::key_encoding::{
+    public_key_to_der, public_key_to_pem, secret_key_to_der, secret_key_to_pem, DEFAULT_OID,
+};
+use lib::mayo_functionality::CompactPublicKey;
+use lib::utils::bytes_to_hex_string;
+
+fn corpus_dir(target: &str) -> PathBuf {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("corpus")
+        .join(target);
+    fs::create_dir_all(&dir).expect("Could not create the corpus directory");
+    dir
+}
+
+fn main() {
+    let path = kat_response_path(VERSION).expect("No KAT file for the parameter set");
+    let kat = read_kat_file(&path).unwrap_or_else(|error| panic!("{}: {}", path.display(), error));
+
+    let verify_dir = corpus_dir("verify");
+    let decode_dir = corpus_dir("decode_keys");
+    let encodings_dir = corpus_dir("encodings");
+
+    for record in &kat.records {
+        let pk = record.pk.as_deref().expect("The KAT file has no pk");
+        let sk: [u8; CSK_BYTES] = record
+            .sk
+            .as_deref()
+            .and_then(|sk| sk.try_into().ok())
+            .expect("The KAT file has no sk");
+        let sm = record.sm.as_deref().expect("The KAT file has no sm");
+        let cpk = CompactPublicKey::from_bytes(pk).unwrap();
+        let name = |suffix: &str| format!("{}_{}{}", VERSION, record.count, suffix);
+
+        let verify_input = [&(SIG_BYTES as u16).to_le_bytes()[..], pk, sm].concat();
+        fs::write(verify_dir.join(name("")), verify_input).unwrap();
+
+        let decode_inputs = [
+            ("_pk", pk.to_vec()),
+            ("_sig", sm[..SIG_BYTES].to_vec()),
+            ("_pk_hex", bytes_to_hex_string(pk, false).into_bytes()),
+            ("_pk_der", public_key_to_der(&cpk, DEFAULT_OID).unwrap()),
+            ("_sk_der", secret_key_to_der(&sk, DEFAULT_OID).unwrap()),
+            (
+                "_pk_pem",
+                public_key_to_pem(&cpk, DEFAULT_OID).unwrap().into_bytes(),
+            ),
+            (
+                "_sk_pem",
+                secret_key_to_pem(&sk, DEFAULT_OID).unwrap().into_bytes(),
+            ),
+        ];
+        for (suffix, input) in decode_inputs {
+            fs::write(decode_dir.join(name(suffix)), input).unwrap();
+        }
+
+        fs::write(encodings_dir.join(name("")), sm).unwrap();
+    }
+
+    println!(
+        "Wrote the seed corpus of {} records of {} to {}",
+        kat.records.len(),
+        path.display(),
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("corpus")
+            .display()
+    );
+}
@@ -0,0 +1,59 @@
+// Deserialization of public keys, secret keys and signatures from untrusted bytes: the NIST byte
+// layout, the hex/base64/raw files of the command line tool (feature `cli`) and the DER/PEM key
+// encodings.
+// Nothing may panic, and whatever decodes must encode back to the same key or signature.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use lib::cli::decode;
+use lib::constants::{CPK_BYTES, CSK_BYTES, K, N, SALT_BYTES, SIG_BYTES};
+use lib::key_encoding::{
+    public_key_from_der, public_key_from_pem, public_key_to_der, secret_key_from_der,
+    secret_key_from_pem, secret_key_to_der, DEFAULT_OID,
+};
+use lib::mayo_functionality::CompactPublicKey;
+use lib::{decode_bytestring_to_array, encode_to_bytestring_array};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(cpk) = CompactPublicKey::from_bytes(data) {
+        assert_eq!(cpk.to_bytes().as_slice(), data);
+    }
+
+    // The signature vector s is packed two elements per byte, for an odd K * N the high nibble
+    // of the last byte is not used
+    if data.len() == SIG_BYTES {
+        let s_bytes = &data[..SIG_BYTES - SALT_BYTES];
+        let s = decode_bytestring_to_array!(s_bytes, K * N);
+        assert!(s.iter().all(|&element| element < 16));
+
+        let mut expected = s_bytes.to_vec();
+        if K * N % 2 == 1 {
+            *expected.last_mut().unwrap() &= 0x0F;
+        }
+        assert_eq!(
+            encode_to_bytestring_array!(s, K * N, SIG_BYTES - SALT_BYTES).to_vec(),
+            expected
+        );
+    }
+
+    for length in [CPK_BYTES, CSK_BYTES, SIG_BYTES] {
+        if let Some(bytes) = decode(data, length) {
+            assert_eq!(bytes.len(), length);
+        }
+    }
+
+    if let Ok(cpk) = public_key_from_der(data, DEFAULT_OID) {
+        let der = public_key_to_der(&cpk, DEFAULT_OID).unwrap();
+        let decoded = public_key_from_der(&der, DEFAULT_OID).unwrap();
+        assert_eq!(decoded.to_bytes(), cpk.to_bytes());
+    }
+    if let Ok(csk) = secret_key_from_der(data, DEFAULT_OID) {
+        let der = secret_key_to_der(&csk, DEFAULT_OID).unwrap();
+        assert_eq!(secret_key_from_der(&der, DEFAULT_OID), Ok(csk));
+    }
+    if let Ok(pem) = std::str::from_utf8(data) {
+        let _ = public_key_from_pem(pem, DEFAULT_OID);
+        let _ = secret_key_from_pem(pem, DEFAULT_OID);
+    }
+});
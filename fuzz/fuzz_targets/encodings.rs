@@ -0,0 +1,48 @@
+// Round trips of the field element encodings on arbitrary input: the bitsliced m-vectors
+// (encode_bit_sliced_array!/decode_bit_sliced_array!) and the packing of two elements per byte
+// (encode_to_bytestring_array!/decode_bytestring_to_array!) for odd and even lengths.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use lib::constants::{M, V};
+use lib::{
+    decode_bit_sliced_array, decode_bytestring_to_array, encode_bit_sliced_array,
+    encode_to_bytestring_array,
+};
+
+// Element counts of both parities (V is even for MAYO_1 and MAYO_2, odd for MAYO_3 and MAYO_5)
+const ODD: usize = V | 1;
+const EVEN: usize = ODD + 1;
+
+fuzz_target!(|data: &[u8]| {
+    // M elements from the low nibbles of the input
+    if data.len() >= M {
+        let mut v = [0u8; M];
+        for (element, byte) in v.iter_mut().zip(data) {
+            *element = byte & 0x0F;
+        }
+
+        // The bitsliced bytes are read back as u32 words of the four bit planes
+        let bytes = encode_bit_sliced_array!(v, M);
+        let words: Vec<u32> = bytes
+            .chunks(4)
+            .map(|word| u32::from_le_bytes(word.try_into().unwrap()))
+            .collect();
+        assert_eq!(decode_bit_sliced_array!(words), v);
+    }
+
+    if data.len() >= EVEN.div_ceil(2) {
+        let odd = decode_bytestring_to_array!(data, ODD);
+        let even = decode_bytestring_to_array!(data, EVEN);
+        assert!(odd.iter().chain(&even).all(|&element| element < 16));
+        assert_eq!(odd[..], even[..ODD]);
+
+        // Encoding restores the bytes, except for the unused high nibble of an odd length
+        let encoded = encode_to_bytestring_array!(even, EVEN, EVEN / 2);
+        assert_eq!(encoded[..], data[..EVEN / 2]);
+        let encoded = encode_to_bytestring_array!(odd, ODD, ODD.div_ceil(2));
+        assert_eq!(encoded[..ODD / 2], data[..ODD / 2]);
+        assert_eq!(encoded[ODD / 2], data[ODD / 2] & 0x0F);
+    }
+});
@@ -0,0 +1,33 @@
+// Verification of arbitrary public keys, signatures and messages, which must never panic.
+// Input: signature length (u16, little endian) || public key || signature || message
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use lib::constants::{CPK_BYTES, SIG_BYTES};
+use lib::mayo_functionality::{api_sign_open, expand_pk, verify, CompactPublicKey};
+
+fuzz_target!(|data: &[u8]| {
+    let Some((length, rest)) = data.split_first_chunk::<2>() else {
+        return;
+    };
+    let signature_length = u16::from_le_bytes(*length) as usize;
+    if rest.len() < CPK_BYTES + signature_length {
+        return;
+    }
+    let (pk, rest) = rest.split_at(CPK_BYTES);
+    let (signature, message) = rest.split_at(signature_length);
+
+    let cpk = CompactPublicKey::from_bytes(pk).expect("The public key has the right length");
+    let valid = verify(expand_pk(cpk.clone()), signature, message);
+    assert!(!valid || signature.len() == SIG_BYTES);
+
+    // The signed message API splits the signature off itself and must agree with verify
+    let (opened_valid, opened) = api_sign_open([signature, message].concat(), cpk);
+    if signature.len() == SIG_BYTES {
+        assert_eq!(valid, opened_valid);
+    }
+    if valid {
+        assert_eq!(opened, message);
+    }
+});
@@ -0,0 +1,416 @@
+// ACVP-style JSON test vectors (feature `acvp`) for keyGen, sigGen and sigVer of the compiled
+// parameter set, laid out like the ACVP ML-DSA vector sets. A prompt
+//
+//   {"vsId": 1, "algorithm": "MAYO", "mode": "keyGen", "revision": "1.0", "testGroups": [
+//     {"tgId": 1, "testType": "AFT", "parameterSet": "MAYO_1", "tests": [{"tcId": 1, "seed": ..}]}
+//   ]}
+//
+// is answered by `respond` with the results under the same ids:
+//
+//   {"vsId": 1, "algorithm": "MAYO", "mode": "keyGen", "revision": "1.0", "testGroups": [
+//     {"tgId": 1, "tests": [{"tcId": 1, "pk": .., "sk": ..}]}
+//   ]}
+//
+// Byte strings are hex. The fields of the tests are
+//   keyGen: seed (SK_SEED_BYTES)                 -> pk, sk
+//   sigGen: sk, message, rnd (R_BYTES), context  -> signature
+//   sigVer: pk, message, signature, context      -> testPassed
+// The context is optional, see `sign_with_context`. In sigGen groups with "deterministic": true
+// the tests have no rnd and are signed with R_BYTES zero bytes. The ACVP array form
+// [{"acvVersion": ..}, {vector set}] of prompts is read as well.
+use std::fmt;
+
+use serde_json::{json, Map, Value};
+
+use crate::constants::{
+    CPK_BYTES, CSK_BYTES, DIGEST_BYTES, R_BYTES, SIG_BYTES, SK_SEED_BYTES, VERSION,
+};
+use crate::mayo_functionality::{
+    compact_key_gen_from_seed, expand_pk, sign_digest_with_randomness, verify_digest,
+    CompactPublicKey,
+};
+use crate::message_hasher::MessageHasher;
+use crate::utils::{bytes_to_hex_string, hex_string_to_bytes};
+
+pub const ACVP_ALGORITHM: &str = "MAYO";
+
+// Fields of the vector set copied from the prompt to the response
+const HEADER_FIELDS: [&str; 4] = ["vsId", "algorithm", "mode", "revision"];
+
+// A prompt that can not be answered. The ids locate the group and test, if known.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AcvpError {
+    pub tg_id: Option<u64>,
+    pub tc_id: Option<u64>,
+    pub message: String,
+}
+
+impl fmt::Display for AcvpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(tg_id) = self.tg_id {
+            write!(f, "tgId {}, ", tg_id)?;
+        }
+        if let Some(tc_id) = self.tc_id {
+            write!(f, "tcId {}, ", tc_id)?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for AcvpError {}
+
+impl AcvpError {
+    fn new(message: impl Into<String>) -> Self {
+        AcvpError {
+            tg_id: None,
+            tc_id: None,
+            message: message.into(),
+        }
+    }
+}
+
+// A field of a response that differs from the expected results
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AcvpMismatch {
+    pub tg_id: u64,
+    pub tc_id: u64,
+    pub field: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl fmt::Display for AcvpMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "tgId {}, tcId {}, {}: expected {}, got {}",
+            self.tg_id, self.tc_id, self.field, self.expected, self.actual
+        )
+    }
+}
+
+// Reads a prompt, response or expected results file
+pub fn parse_acvp(contents: &str) -> Result<Value, AcvpError> {
+    let value: Value = serde_json::from_str(contents)
+        .map_err(|error| AcvpError::new(format!("Invalid JSON: {}", error)))?;
+    vector_set(&value).cloned()
+}
+
+// The vector set of a plain or an ACVP array form file
+fn vector_set(value: &Value) -> Result<&Value, AcvpError> {
+    let vector_set = match value {
+        Value::Array(elements) => elements
+            .iter()
+            .find(|element| element.get("testGroups").is_some()),
+        _ => Some(value),
+    };
+    vector_set
+        .filter(|vector_set| vector_set["testGroups"].is_array())
+        .ok_or_else(|| AcvpError::new("No testGroups found"))
+}
+
+// Answers every test of a prompt with the compiled parameter set
+pub fn respond(prompt: &Value) -> Result<Value, AcvpError> {
+    let vector_set = vector_set(prompt)?;
+    if let Some(algorithm) = vector_set.get("algorithm") {
+        if algorithm != ACVP_ALGORITHM {
+            return Err(AcvpError::new(format!(
+                "Unsupported algorithm {}",
+                algorithm
+            )));
+        }
+    }
+    let mode = vector_set["mode"]
+        .as_str()
+        .ok_or_else(|| AcvpError::new("Missing mode"))?;
+
+    let mut response = Map::new();
+    for field in HEADER_FIELDS {
+        if let Some(value) = vector_set.get(field) {
+            response.insert(field.to_string(), value.clone());
+        }
+    }
+
+    let mut groups = Vec::new();
+    for group in vector_set["testGroups"].as_array().into_iter().flatten() {
+        let tg_id = group["tgId"]
+            .as_u64()
+            .ok_or_else(|| AcvpError::new("A test group has no tgId"))?;
+        let group_error = |message: &str| AcvpError {
+            tg_id: Some(tg_id),
+            tc_id: None,
+            message: message.to_string(),
+        };
+
+        let parameter_set = group["parameterSet"].as_str();
+        if parameter_set != Some(VERSION) {
+            return Err(group_error(&format!(
+                "The parameter set {} is not {}, the parameter set of this build",
+                parameter_set.unwrap_or("(missing)"),
+                VERSION
+            )));
+        }
+        let deterministic = group["deterministic"].as_bool().unwrap_or(false);
+
+        let mut tests = Vec::new();
+        for test in group["tests"]
+            .as_array()
+            .ok_or_else(|| group_error("Missing tests"))?
+        {
+            let tc_id = test["tcId"]
+                .as_u64()
+                .ok_or_else(|| group_error("A test has no tcId"))?;
+            let result = match mode {
+                "keyGen" => key_gen_test(test),
+                "sigGen" => sig_gen_test(test, deterministic),
+                "sigVer" => sig_ver_test(test),
+                _ => return Err(AcvpError::new(format!("Unsupported mode {}", mode))),
+            };
+            let result = result.map_err(|message| AcvpError {
+                tg_id: Some(tg_id),
+                tc_id: Some(tc_id),
+                message,
+            })?;
+
+            let mut test = Map::new();
+            test.insert("tcId".to_string(), json!(tc_id));
+            test.extend(result);
+            tests.push(Value::Object(test));
+        }
+        groups.push(json!({ "tgId": tg_id, "tests": tests }));
+    }
+    response.insert("testGroups".to_string(), Value::Array(groups));
+
+    Ok(Value::Object(response))
+}
+
+// A hex field of a test, of `length` bytes if given
+fn hex_field(test: &Value, name: &str, length: Option<usize>) -> Result<Vec<u8>, String> {
+    let hex = test[name]
+        .as_str()
+        .ok_or_else(|| format!("Missing {}", name))?;
+    if hex.len() % 2 != 0 || !hex.bytes().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("{} is not a hex string", name));
+    }
+    let bytes = hex_string_to_bytes(hex);
+    match length {
+        Some(length) if bytes.len() != length => Err(format!("{} has incorrect length", name)),
+        _ => Ok(bytes),
+    }
+}
+
+fn hex(bytes: &[u8]) -> Value {
+    Value::String(bytes_to_hex_string(bytes, false))
+}
+
+// The message of a test and its context, empty if it has none
+fn message_and_context(test: &Value) -> Result<(Vec<u8>, Vec<u8>), String> {
+    let message = hex_field(test, "message", None)?;
+    let context = match test.get("context") {
+        Some(_) => hex_field(test, "context", None)?,
+        None => Vec::new(),
+    };
+    Ok((message, context))
+}
+
+fn message_digest(message: &[u8], context: &[u8]) -> Result<[u8; DIGEST_BYTES], &'static str> {
+    let mut hasher = MessageHasher::with_context(context)?;
+    hasher.update(message);
+    Ok(hasher.finalize())
+}
+
+fn key_gen_test(test: &Value) -> Result<Map<String, Value>, String> {
+    let seed = hex_field(test, "seed", Some(SK_SEED_BYTES))?;
+    let (cpk, csk) = compact_key_gen_from_seed(seed.try_into().unwrap());
+
+    let mut result = Map::new();
+    result.insert("pk".to_string(), hex(&cpk.to_bytes()));
+    result.insert("sk".to_string(), hex(&csk));
+    Ok(result)
+}
+
+fn sig_gen_test(test: &Value, deterministic: bool) -> Result<Map<String, Value>, String> {
+    let sk = hex_field(test, "sk", Some(CSK_BYTES))?;
+    let rnd = match deterministic {
+        true => vec![0u8; R_BYTES],
+        false => hex_field(test, "rnd", Some(R_BYTES))?,
+    };
+    let (message, context) = message_and_context(test)?;
+    let digest = message_digest(&message, &context).map_err(str::to_string)?;
+    let signature =
+        sign_digest_with_randomness(sk.try_into().unwrap(), &digest, rnd.try_into().unwrap());
+
+    let mut result = Map::new();
+    result.insert("signature".to_string(), hex(&signature));
+    Ok(result)
+}
+
+fn sig_ver_test(test: &Value) -> Result<Map<String, Value>, String> {
+    let cpk = CompactPublicKey::from_bytes(&hex_field(test, "pk", Some(CPK_BYTES))?)
+        .map_err(str::to_string)?;
+    let signature = hex_field(test, "signature", None)?;
+    let (message, context) = message_and_context(test)?;
+
+    // Signatures of the wrong length and invalid contexts fail like any other invalid signature
+    let passed = signature.len() == SIG_BYTES
+        && match message_digest(&message, &context) {
+            Ok(digest) => verify_digest(expand_pk(cpk), &signature, &digest),
+            Err(_) => false,
+        };
+
+    let mut result = Map::new();
+    result.insert("testPassed".to_string(), json!(passed));
+    Ok(result)
+}
+
+// Compares a response with the expected results of the same prompt. Every field of every
+// expected test must be present with the same value, hex strings are compared ignoring case.
+pub fn compare(expected: &Value, response: &Value) -> Result<Vec<AcvpMismatch>, AcvpError> {
+    let expected = vector_set(expected)?;
+    let response = vector_set(response)?;
+
+    let find_test = |tg_id: u64, tc_id: u64| {
+        response["testGroups"]
+            .as_array()?
+            .iter()
+            .find(|group| group["tgId"].as_u64() == Some(tg_id))?["tests"]
+            .as_array()?
+            .iter()
+            .find(|test| test["tcId"].as_u64() == Some(tc_id))
+    };
+
+    let mut mismatches = Vec::new();
+    for group in expected["testGroups"].as_array().into_iter().flatten() {
+        let tg_id = group["tgId"]
+            .as_u64()
+            .ok_or_else(|| AcvpError::new("An expected test group has no tgId"))?;
+
+        for expected_test in group["tests"].as_array().into_iter().flatten() {
+            let tc_id = expected_test["tcId"].as_u64().ok_or_else(|| AcvpError {
+                tg_id: Some(tg_id),
+                tc_id: None,
+                message: "An expected test has no tcId".to_string(),
+            })?;
+            let mut mismatch = |field: &str, expected: String, actual: String| {
+                mismatches.push(AcvpMismatch {
+                    tg_id,
+                    tc_id,
+                    field: field.to_string(),
+                    expected,
+                    actual,
+                })
+            };
+
+            let Some(test) = find_test(tg_id, tc_id) else {
+                mismatch("tcId", "a result".to_string(), "none".to_string());
+                continue;
+            };
+            for (field, expected_value) in expected_test.as_object().into_iter().flatten() {
+                let value = &test[field.as_str()];
+                let matches = match (expected_value.as_str(), value.as_str()) {
+                    (Some(expected_hex), Some(hex)) => expected_hex.eq_ignore_ascii_case(hex),
+                    _ => expected_value == value,
+                };
+                if !matches {
+                    mismatch(field, expected_value.to_string(), value.to_string());
+                }
+            }
+        }
+    }
+    Ok(mismatches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prompt(mode: &str, parameter_set: &str, test: Value) -> Value {
+        json!({
+            "vsId": 7,
+            "algorithm": "MAYO",
+            "mode": mode,
+            "revision": "1.0",
+            "testGroups": [
+                {"tgId": 2, "testType": "AFT", "parameterSet": parameter_set, "tests": [test]}
+            ]
+        })
+    }
+
+    #[test]
+    fn test_sign_and_verify_round_trip() {
+        let seed = bytes_to_hex_string(&[3u8; SK_SEED_BYTES], false);
+        let key_gen =
+            respond(&prompt("keyGen", VERSION, json!({"tcId": 1, "seed": seed}))).unwrap();
+        assert_eq!(key_gen["vsId"], 7);
+        assert_eq!(key_gen["mode"], "keyGen");
+        let keys = &key_gen["testGroups"][0]["tests"][0];
+        assert_eq!(keys["tcId"], 1);
+
+        let mut sig_gen = prompt(
+            "sigGen",
+            VERSION,
+            json!({"tcId": 4, "sk": keys["sk"], "message": "0102", "context": "AA"}),
+        );
+        sig_gen["testGroups"][0]["deterministic"] = json!(true);
+        let signature = &respond(&sig_gen).unwrap()["testGroups"][0]["tests"][0]["signature"];
+
+        let verify = |message: &str, signature: &Value| {
+            let test = json!({
+                "tcId": 5,
+                "pk": keys["pk"],
+                "message": message,
+                "signature": signature,
+                "context": "AA",
+            });
+            respond(&prompt("sigVer", VERSION, test)).unwrap()["testGroups"][0]["tests"][0]
+                ["testPassed"]
+                .clone()
+        };
+        assert_eq!(verify("0102", signature), json!(true));
+        assert_eq!(verify("0103", signature), json!(false));
+        assert_eq!(verify("0102", &json!("00")), json!(false));
+
+        // The response matches itself as expected results, but not a changed one
+        let response = respond(&sig_gen).unwrap();
+        assert_eq!(compare(&response, &response), Ok(vec![]));
+        let mut expected = response.clone();
+        expected["testGroups"][0]["tests"][0]["signature"] = json!("00");
+        let mismatches = compare(&expected, &response).unwrap();
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(
+            (mismatches[0].tc_id, mismatches[0].field.as_str()),
+            (4, "signature")
+        );
+    }
+
+    #[test]
+    fn test_invalid_prompts() {
+        let other = if VERSION == "MAYO_1" {
+            "MAYO_2"
+        } else {
+            "MAYO_1"
+        };
+        let error =
+            respond(&prompt("keyGen", other, json!({"tcId": 1, "seed": "00"}))).unwrap_err();
+        assert_eq!(error.tg_id, Some(2));
+
+        let error =
+            respond(&prompt("keyGen", VERSION, json!({"tcId": 3, "seed": "00"}))).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "tgId 2, tcId 3, seed has incorrect length"
+        );
+
+        let error =
+            respond(&prompt("keyGen", VERSION, json!({"tcId": 3, "seed": "XY"}))).unwrap_err();
+        assert_eq!(error.message, "seed is not a hex string");
+
+        assert!(respond(&prompt("sigCheck", VERSION, json!({"tcId": 1}))).is_err());
+        assert!(parse_acvp("{\"vsId\": 1}").is_err());
+
+        // The ACVP array form
+        let vector_set = prompt("keyGen", VERSION, json!({"tcId": 1}));
+        let file = json!([{"acvVersion": "1.0"}, vector_set]).to_string();
+        assert_eq!(parse_acvp(&file), Ok(vector_set));
+    }
+}
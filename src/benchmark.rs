@@ -1,15 +1,65 @@
 use std::fs::{self};
 use std::time::{Duration, Instant};
 
-use crate::constants::VERSION;
+use crate::constants::{K, M, O, VERSION};
+use std::alloc::{GlobalAlloc, Layout, System};
 use std::fs::OpenOptions;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use crate::crypto_primitives::{safe_random_bytes, safe_random_bytes_init};
-use crate::mayo_functionality::{api_sign, api_sign_open, compact_key_gen, expand_pk, expand_sk};
+use crate::matrix::Vector;
+use crate::mayo_functionality::{
+    api_sign, api_sign_open, compact_key_gen, expand_pk, expand_sk, linear_system,
+    linear_system_unpacked, sign, sign_batch, sign_low_memory,
+};
+use crate::utils::random_words;
 
 use csv::Writer;
 use std::error::Error;
 
+// Size (in bytes) of the chunks of P1 and P2 rows held by sign_low_memory during the benchmark.
+// At least MIN_CHUNK_BYTES of every parameter set.
+const CHUNK_BYTES: usize = 16 * 1024;
+
+// Number of messages signed under one key in the batch signing benchmark
+const BATCH_SIZE: usize = 100;
+const BATCH_ITERATIONS: i32 = 10;
+
+// Size of the painted stack the measured function runs on, and its alignment (a multiple of the
+// page size, as required by pthread_attr_setstack on macOS)
+#[cfg(unix)]
+const MEASURED_STACK_BYTES: usize = 16 * 1024 * 1024;
+#[cfg(unix)]
+const MEASURED_STACK_ALIGN: usize = 64 * 1024;
+#[cfg(unix)]
+const STACK_PAINT: u8 = 0xA5;
+
+// Allocator that keeps track of the current and peak number of heap bytes in use.
+// Only installed for benchmark builds.
+pub struct TrackingAllocator;
+
+static HEAP_CURRENT: AtomicUsize = AtomicUsize::new(0);
+static HEAP_PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for TrackingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let now = HEAP_CURRENT.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+            HEAP_PEAK.fetch_max(now, Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        HEAP_CURRENT.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: TrackingAllocator = TrackingAllocator;
+
 #[allow(unused_mut, unused_assignments)]
 pub fn benchmark(amount_of_iterations: i32) -> Result<(), Box<dyn Error>> {
     let implementation_variant = "armv8_bitsliced_implementation";
@@ -47,6 +97,15 @@ pub fn benchmark(amount_of_iterations: i32) -> Result<(), Box<dyn Error>> {
         "expand_pk",
         "sign+expand_sk",
         "verify+expand_pk",
+        "sign_low_memory",
+        "sign_peak_stack_bytes",
+        "sign_peak_heap_bytes",
+        "sign_low_memory_peak_stack_bytes",
+        "sign_low_memory_peak_heap_bytes",
+        "api_sign_batch_100",
+        "sign_batch_100",
+        "linear_system_unpacked",
+        "linear_system",
     ])?;
 
     // Flush data to file
@@ -69,6 +128,11 @@ pub fn benchmark(amount_of_iterations: i32) -> Result<(), Box<dyn Error>> {
     let mut durations_expand_pk = Vec::with_capacity(1000);
     let mut durations_sign = Vec::with_capacity(1000);
     let mut durations_verify = Vec::with_capacity(1000);
+    let mut durations_sign_low_memory = Vec::with_capacity(1000);
+    let mut durations_api_sign_batch = Vec::with_capacity(1000);
+    let mut durations_sign_batch = Vec::with_capacity(1000);
+    let mut durations_linear_system_unpacked = Vec::with_capacity(1000);
+    let mut durations_linear_system = Vec::with_capacity(1000);
 
     let warm_up_iterations = 50;
 
@@ -165,6 +229,83 @@ pub fn benchmark(amount_of_iterations: i32) -> Result<(), Box<dyn Error>> {
         durations_verify.push(duration_verify);
     }
 
+    // Low-memory sign benchmark
+    for _ in 0..warm_up_iterations {
+        let (_, csk) = compact_key_gen();
+        let mut message = [0u8; 32];
+        safe_random_bytes(&mut message, 32);
+        let _ = sign_low_memory(csk, &message, CHUNK_BYTES);
+    }
+
+    for _ in 0..amount_of_iterations {
+        // Setup
+        let (_, csk) = compact_key_gen();
+        let mut message = [0u8; 32];
+        safe_random_bytes(&mut message, 32);
+
+        let start_sign_low_memory = Instant::now(); // Start timer
+        sign_low_memory(csk, &message, CHUNK_BYTES)?;
+        let duration_sign_low_memory = start_sign_low_memory.elapsed(); // Stop timer
+
+        durations_sign_low_memory.push(duration_sign_low_memory);
+    }
+
+    // Batch signing benchmark: BATCH_SIZE messages with repeated api_sign calls vs. sign_batch
+    let mut batch_messages = vec![[0u8; 32]; BATCH_SIZE];
+    for message in batch_messages.iter_mut() {
+        safe_random_bytes(message, 32);
+    }
+    let batch_message_refs: Vec<&[u8]> = batch_messages.iter().map(|m| m.as_slice()).collect();
+
+    for _ in 0..BATCH_ITERATIONS {
+        // Setup
+        let (_, csk) = compact_key_gen();
+
+        let start_api_sign_batch = Instant::now(); // Start timer
+        for message in batch_messages.iter() {
+            api_sign(message.to_vec(), csk);
+        }
+        let duration_api_sign_batch = start_api_sign_batch.elapsed(); // Stop timer
+
+        // Expanding the secret key is part of the batch
+        let start_sign_batch = Instant::now(); // Start timer
+        sign_batch(&expand_sk(csk), &batch_message_refs);
+        let duration_sign_batch = start_sign_batch.elapsed(); // Stop timer
+
+        durations_api_sign_batch.push(duration_api_sign_batch);
+        durations_sign_batch.push(duration_sign_batch);
+    }
+
+    // Linear system benchmark: building A and y in sign on unpacked elements (before) vs. on
+    // bitsliced elements straight into the packed rows of (A | y) (after), for random M_i and
+    // v^t * P1 * v
+    for _ in 0..amount_of_iterations {
+        // Setup
+        let m_matrices_array = random_words::<{ K * O * M / 8 }>();
+        let vt_p1_v = random_words::<{ K * K * M / 8 }>();
+        let t = Vector::<M>::zero();
+
+        let start_linear_system_unpacked = Instant::now(); // Start timer
+        std::hint::black_box(linear_system_unpacked(&m_matrices_array, &vt_p1_v, t));
+        let duration_linear_system_unpacked = start_linear_system_unpacked.elapsed(); // Stop timer
+
+        let start_linear_system = Instant::now(); // Start timer
+        std::hint::black_box(linear_system(&m_matrices_array, &vt_p1_v, t));
+        let duration_linear_system = start_linear_system.elapsed(); // Stop timer
+
+        durations_linear_system_unpacked.push(duration_linear_system_unpacked);
+        durations_linear_system.push(duration_linear_system);
+    }
+
+    // Peak memory of sign and low-memory sign
+    let (_, csk) = compact_key_gen();
+    let (sign_peak_stack, sign_peak_heap) = measure_peak_memory(move || {
+        sign(csk, &[0u8; 32]);
+    });
+    let (sign_low_memory_peak_stack, sign_low_memory_peak_heap) = measure_peak_memory(move || {
+        let _ = sign_low_memory(csk, &[0u8; 32], CHUNK_BYTES);
+    });
+
     let var = 10_f64;
     let _ = format_duration_as_string(&var);
 
@@ -173,12 +314,23 @@ pub fn benchmark(amount_of_iterations: i32) -> Result<(), Box<dyn Error>> {
     durations_expand_pk.sort();
     durations_sign.sort();
     durations_verify.sort();
+    durations_sign_low_memory.sort();
+    durations_api_sign_batch.sort();
+    durations_sign_batch.sort();
+    durations_linear_system_unpacked.sort();
+    durations_linear_system.sort();
 
     let final_median_duration_keygen = find_median(&durations_keygen);
     let final_median_duration_expand_sk = find_median(&durations_expand_sk);
     let final_median_duration_expand_pk = find_median(&durations_expand_pk);
     let final_median_duration_sign = find_median(&durations_sign);
     let final_median_duration_verify = find_median(&durations_verify);
+    let final_median_duration_sign_low_memory = find_median(&durations_sign_low_memory);
+    let final_median_duration_api_sign_batch = find_median(&durations_api_sign_batch);
+    let final_median_duration_sign_batch = find_median(&durations_sign_batch);
+    let final_median_duration_linear_system_unpacked =
+        find_median(&durations_linear_system_unpacked);
+    let final_median_duration_linear_system = find_median(&durations_linear_system);
 
     let mut res_median_duration_keygen = format_duration_as_nanos(&final_median_duration_keygen);
     let mut res_median_duration_expand_sk =
@@ -187,6 +339,16 @@ pub fn benchmark(amount_of_iterations: i32) -> Result<(), Box<dyn Error>> {
         format_duration_as_nanos(&final_median_duration_expand_pk);
     let mut res_median_duration_sign = format_duration_as_nanos(&final_median_duration_sign);
     let mut res_median_duration_verify = format_duration_as_nanos(&final_median_duration_verify);
+    let mut res_median_duration_sign_low_memory =
+        format_duration_as_nanos(&final_median_duration_sign_low_memory);
+    let mut res_median_duration_api_sign_batch =
+        format_duration_as_nanos(&final_median_duration_api_sign_batch);
+    let mut res_median_duration_sign_batch =
+        format_duration_as_nanos(&final_median_duration_sign_batch);
+    let mut res_median_duration_linear_system_unpacked =
+        format_duration_as_nanos(&final_median_duration_linear_system_unpacked);
+    let mut res_median_duration_linear_system =
+        format_duration_as_nanos(&final_median_duration_linear_system);
 
     #[cfg(feature = "CCM1")]
     {
@@ -209,6 +371,23 @@ pub fn benchmark(amount_of_iterations: i32) -> Result<(), Box<dyn Error>> {
         res_median_duration_verify = format_duration_as_string(
             &(cpu_speed_hz * (final_median_duration_verify.as_nanos() as f64 / 1e9) as f64),
         );
+        res_median_duration_sign_low_memory = format_duration_as_string(
+            &(cpu_speed_hz
+                * (final_median_duration_sign_low_memory.as_nanos() as f64 / 1e9) as f64),
+        );
+        res_median_duration_api_sign_batch = format_duration_as_string(
+            &(cpu_speed_hz * (final_median_duration_api_sign_batch.as_nanos() as f64 / 1e9) as f64),
+        );
+        res_median_duration_sign_batch = format_duration_as_string(
+            &(cpu_speed_hz * (final_median_duration_sign_batch.as_nanos() as f64 / 1e9) as f64),
+        );
+        res_median_duration_linear_system_unpacked = format_duration_as_string(
+            &(cpu_speed_hz
+                * (final_median_duration_linear_system_unpacked.as_nanos() as f64 / 1e9) as f64),
+        );
+        res_median_duration_linear_system = format_duration_as_string(
+            &(cpu_speed_hz * (final_median_duration_linear_system.as_nanos() as f64 / 1e9) as f64),
+        );
     }
 
     #[cfg(feature = "CCODROID-C4")]
@@ -232,6 +411,23 @@ pub fn benchmark(amount_of_iterations: i32) -> Result<(), Box<dyn Error>> {
         res_median_duration_verify = format_duration_as_string(
             &(cpu_speed_hz * (final_median_duration_verify.as_nanos() as f64 / 1e9) as f64),
         );
+        res_median_duration_sign_low_memory = format_duration_as_string(
+            &(cpu_speed_hz
+                * (final_median_duration_sign_low_memory.as_nanos() as f64 / 1e9) as f64),
+        );
+        res_median_duration_api_sign_batch = format_duration_as_string(
+            &(cpu_speed_hz * (final_median_duration_api_sign_batch.as_nanos() as f64 / 1e9) as f64),
+        );
+        res_median_duration_sign_batch = format_duration_as_string(
+            &(cpu_speed_hz * (final_median_duration_sign_batch.as_nanos() as f64 / 1e9) as f64),
+        );
+        res_median_duration_linear_system_unpacked = format_duration_as_string(
+            &(cpu_speed_hz
+                * (final_median_duration_linear_system_unpacked.as_nanos() as f64 / 1e9) as f64),
+        );
+        res_median_duration_linear_system = format_duration_as_string(
+            &(cpu_speed_hz * (final_median_duration_linear_system.as_nanos() as f64 / 1e9) as f64),
+        );
     }
 
     wtr.write_record([
@@ -241,6 +437,15 @@ pub fn benchmark(amount_of_iterations: i32) -> Result<(), Box<dyn Error>> {
         &res_median_duration_expand_pk,
         &res_median_duration_sign,
         &res_median_duration_verify,
+        &res_median_duration_sign_low_memory,
+        &format_stack_bytes(sign_peak_stack),
+        &sign_peak_heap.to_string(),
+        &format_stack_bytes(sign_low_memory_peak_stack),
+        &sign_low_memory_peak_heap.to_string(),
+        &res_median_duration_api_sign_batch,
+        &res_median_duration_sign_batch,
+        &res_median_duration_linear_system_unpacked,
+        &res_median_duration_linear_system,
     ])?;
 
     wtr.flush()?;
@@ -272,4 +477,98 @@ fn find_median(durations: &[Duration]) -> Duration {
         // Odd number of elements, take the middle element
         durations[len / 2]
     }
-}
\ No newline at end of file
+}
+
+// Runs `f` on a fresh thread and returns its peak stack and heap usage in bytes.
+// On unix the thread runs on a stack allocated here and painted with STACK_PAINT beforehand
+// (pthread_attr_setstack), so once it has been joined the deepest overwritten byte of that
+// allocation gives the peak stack usage, including the thread's TLS block. Elsewhere the stack is
+// not measured. Heap usage is only tracked in benchmark builds.
+#[cfg(unix)]
+pub fn measure_peak_memory<F: FnOnce() + Send + 'static>(f: F) -> (Option<usize>, usize) {
+    struct Task {
+        f: Option<Box<dyn FnOnce() + Send>>,
+        heap_peak: usize,
+        panicked: bool,
+    }
+
+    extern "C" fn run(task: *mut libc::c_void) -> *mut libc::c_void {
+        let task = unsafe { &mut *(task as *mut Task) };
+        let f = task.f.take().expect("Task already ran");
+
+        let heap_start = HEAP_CURRENT.load(Ordering::SeqCst);
+        HEAP_PEAK.store(heap_start, Ordering::SeqCst);
+
+        // A panic must not unwind out of the thread's start routine
+        task.panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).is_err();
+        task.heap_peak = HEAP_PEAK.load(Ordering::SeqCst) - heap_start;
+        std::ptr::null_mut()
+    }
+
+    let layout = Layout::from_size_align(MEASURED_STACK_BYTES, MEASURED_STACK_ALIGN)
+        .expect("Invalid stack layout");
+    let stack = unsafe { System.alloc(layout) };
+    assert!(!stack.is_null(), "Failed to allocate the measured stack");
+    unsafe { std::ptr::write_bytes(stack, STACK_PAINT, MEASURED_STACK_BYTES) };
+
+    let mut task = Task {
+        f: Some(Box::new(f)),
+        heap_peak: 0,
+        panicked: false,
+    };
+    unsafe {
+        let mut attr: libc::pthread_attr_t = std::mem::zeroed();
+        assert_eq!(libc::pthread_attr_init(&mut attr), 0);
+        assert_eq!(
+            libc::pthread_attr_setstack(
+                &mut attr,
+                stack as *mut libc::c_void,
+                MEASURED_STACK_BYTES
+            ),
+            0
+        );
+        let mut thread: libc::pthread_t = std::mem::zeroed();
+        assert_eq!(
+            libc::pthread_create(
+                &mut thread,
+                &attr,
+                run,
+                &mut task as *mut Task as *mut libc::c_void
+            ),
+            0,
+            "Failed to spawn measurement thread"
+        );
+        assert_eq!(libc::pthread_join(thread, std::ptr::null_mut()), 0);
+        libc::pthread_attr_destroy(&mut attr);
+    }
+
+    // The stack grows down from the end of the allocation
+    let painted = unsafe { std::slice::from_raw_parts(stack, MEASURED_STACK_BYTES) };
+    let untouched = painted
+        .iter()
+        .take_while(|byte| **byte == STACK_PAINT)
+        .count();
+    unsafe { System.dealloc(stack, layout) };
+
+    assert!(!task.panicked, "Measurement thread panicked");
+    (Some(MEASURED_STACK_BYTES - untouched), task.heap_peak)
+}
+
+#[cfg(not(unix))]
+pub fn measure_peak_memory<F: FnOnce() + Send + 'static>(f: F) -> (Option<usize>, usize) {
+    std::thread::spawn(move || {
+        let heap_start = HEAP_CURRENT.load(Ordering::SeqCst);
+        HEAP_PEAK.store(heap_start, Ordering::SeqCst);
+
+        f();
+
+        (None, HEAP_PEAK.load(Ordering::SeqCst) - heap_start)
+    })
+    .join()
+    .expect("Measurement thread panicked")
+}
+
+// Peak stack usage for the CSV file, "n/a" where it is not measured
+fn format_stack_bytes(bytes: Option<usize>) -> String {
+    bytes.map_or("n/a".to_string(), |bytes| bytes.to_string())
+}
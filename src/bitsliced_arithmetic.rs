@@ -71,7 +71,7 @@ macro_rules! transposed_mat_mul_bitsliced_mat_add {
         for r in 0..$mat_cols {
             // Transpose means we treat each column of $mat as a row
             for c in (0..$mat_rows) {
-                for k in 0..$mat_cols {
+                for k in 0..$bs_mat_cols {
                     let bs_mat_start_idx = (c * $bs_mat_cols + k) * U32_PER_IDX;
                     let acc_start_idx = (r * $bs_mat_cols + k) * U32_PER_IDX;
 
@@ -231,6 +231,47 @@ macro_rules! mat_mul_bitsliced_mat_add {
     }};
 }
 
+// Multiplies the bitsliced m-vector at `input_start` with `nibble` and adds the result into `acc` at `acc_start`.
+// Dispatches to the intrinsic for the selected parameter set.
+pub fn mul_add_bitsliced_m_vec(
+    input: &[u32],
+    input_start: usize,
+    nibble: u8,
+    acc: &mut [u32],
+    acc_start: usize,
+) {
+    #[cfg(any(feature = "mayo1", feature = "mayo2"))]
+    {
+        safe_mul_add_bitsliced_m_vec_mayo12(
+            input,
+            input_start.try_into().unwrap(),
+            nibble,
+            acc,
+            acc_start.try_into().unwrap(),
+        );
+    }
+    #[cfg(feature = "mayo3")]
+    {
+        safe_mul_add_bitsliced_m_vec_mayo3(
+            input,
+            input_start.try_into().unwrap(),
+            nibble,
+            acc,
+            acc_start.try_into().unwrap(),
+        );
+    }
+    #[cfg(feature = "mayo5")]
+    {
+        safe_mul_add_bitsliced_m_vec_mayo5(
+            input,
+            input_start.try_into().unwrap(),
+            nibble,
+            acc,
+            acc_start.try_into().unwrap(),
+        );
+    }
+}
+
 pub fn p1_add_p1t(p1: &[u32], p1_p1t_added: &mut [u32]) {
     let mut entries_used = 0;
     // Add P1 and P1 transposed
@@ -253,9 +294,9 @@ pub fn p1_add_p1t(p1: &[u32], p1_p1t_added: &mut [u32]) {
 }
 
 pub fn calculate_st_p(
-    p1: [u32; P1_BYTES / 4],
-    p2: [u32; P2_BYTES / 4],
-    p3: [u32; P3_BYTES / 4],
+    p1: &[u32; P1_BYTES / 4],
+    p2: &[u32; P2_BYTES / 4],
+    p3: &[u32; P3_BYTES / 4],
     s: [[u8; N]; K],
 ) -> [u32; N * K * M / 8] {
     let mut st_p = [0u32; N * K * M / 8];
@@ -276,9 +317,319 @@ pub fn calculate_st_p(
 
     const P3_OFFSET: usize = V * K * U32_PER_TERM * 4;
 
-    bitsliced_mat_mul_transposed_mat_add!(p1, s1, &mut st_p, V, V, K, 0, true); // P1 * S1
-    bitsliced_mat_mul_transposed_mat_add!(p2, s2, &mut st_p, V, O, K, 0, false); // P2 * S2
-    bitsliced_mat_mul_transposed_mat_add!(p3, s2, &mut st_p, O, O, K, P3_OFFSET, true); // P3 * S2
+    bitsliced_mat_mul_transposed_mat_add!(*p1, s1, &mut st_p, V, V, K, 0, true); // P1 * S1
+    bitsliced_mat_mul_transposed_mat_add!(*p2, s2, &mut st_p, V, O, K, 0, false); // P2 * S2
+    bitsliced_mat_mul_transposed_mat_add!(*p3, s2, &mut st_p, O, O, K, P3_OFFSET, true); // P3 * S2
 
     st_p
-}
\ No newline at end of file
+}
+
+// Differential tests of the bitsliced routines against plain matrix arithmetic on the M matrices
+// [[[u8; cols]; rows]; M] (held in a Vec, as the larger ones do not fit on the stack)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto_primitives::safe_shake256;
+    use crate::finite_field::{add, mul};
+    use crate::{
+        decode_bit_sliced_array, encode_bit_sliced_array, matrix_add, matrix_mul,
+        transpose_matrix_array,
+    };
+    use proptest::prelude::*;
+
+    // Field elements expanded from a seed, two per byte of SHAKE256 output
+    fn random_elements(seed: u64, len: usize) -> Vec<u8> {
+        let byte_len = len.div_ceil(2);
+        let mut bytes = vec![0u8; byte_len];
+        safe_shake256(&mut bytes, byte_len as u64, &seed.to_le_bytes(), 8);
+        bytes
+            .iter()
+            .flat_map(|byte| [byte & 0xf, byte >> 4])
+            .take(len)
+            .collect()
+    }
+
+    fn random_matrix<const R: usize, const C: usize>(seed: u64) -> [[u8; C]; R] {
+        let mut matrix = [[0u8; C]; R];
+        for (row, chunk) in matrix
+            .iter_mut()
+            .zip(random_elements(seed, R * C).chunks(C))
+        {
+            row.copy_from_slice(chunk);
+        }
+        matrix
+    }
+
+    // M random matrices, zero below the diagonal if upper triangular
+    fn random_matrices<const R: usize, const C: usize>(
+        seed: u64,
+        upper_triangular: bool,
+    ) -> Vec<[[u8; C]; R]> {
+        let elements = random_elements(seed, M * R * C);
+        let mut matrices = vec![[[0u8; C]; R]; M];
+        for (matrix, chunk) in matrices.iter_mut().zip(elements.chunks(R * C)) {
+            for (i, row) in matrix.iter_mut().enumerate() {
+                for (j, entry) in row.iter_mut().enumerate() {
+                    if i <= j || !upper_triangular {
+                        *entry = chunk[i * C + j];
+                    }
+                }
+            }
+        }
+        matrices
+    }
+
+    // Entry (i, j) of all M matrices is one bitsliced m-vector. The entries are stored row by
+    // row, without those below the diagonal if upper triangular.
+    fn bitslice<const R: usize, const C: usize>(
+        matrices: &[[[u8; C]; R]],
+        upper_triangular: bool,
+    ) -> Vec<u32> {
+        let mut words = Vec::new();
+        for i in 0..R {
+            for j in 0..C {
+                if i <= j || !upper_triangular {
+                    let entry: Vec<u8> = matrices.iter().map(|matrix| matrix[i][j]).collect();
+                    let bytes = encode_bit_sliced_array!(entry, M);
+                    words.extend(
+                        bytes
+                            .chunks(4)
+                            .map(|word| u32::from_le_bytes(word.try_into().unwrap())),
+                    );
+                }
+            }
+        }
+        words
+    }
+
+    fn unbitslice<const R: usize, const C: usize>(
+        words: &[u32],
+        upper_triangular: bool,
+    ) -> Vec<[[u8; C]; R]> {
+        let mut matrices = vec![[[0u8; C]; R]; M];
+        let mut entries = words.chunks(U32_PER_IDX);
+        for i in 0..R {
+            for j in 0..C {
+                if i <= j || !upper_triangular {
+                    let entry = entries.next().unwrap();
+                    let decoded = decode_bit_sliced_array!(entry);
+                    for (matrix, element) in matrices.iter_mut().zip(decoded) {
+                        matrix[i][j] = element;
+                    }
+                }
+            }
+        }
+        assert!(entries.next().is_none());
+        matrices
+    }
+
+    fn matrix_product<const R: usize, const C: usize, const BC: usize>(
+        a: &[[u8; C]; R],
+        b: &[[u8; BC]; C],
+    ) -> [[u8; BC]; R] {
+        matrix_mul!(a, R, C, b, BC)
+    }
+
+    fn transpose<const R: usize, const C: usize>(a: &[[u8; C]; R]) -> [[u8; R]; C] {
+        transpose_matrix_array!(a, R, C)
+    }
+
+    // acc_i = acc_i + product_i for each of the M matrices
+    fn add_products<const R: usize, const C: usize>(
+        acc: &mut [[[u8; C]; R]],
+        product: impl Fn(usize) -> [[u8; C]; R],
+    ) {
+        for (i, acc) in acc.iter_mut().enumerate() {
+            let product = product(i);
+            matrix_add!(acc, product, R, C);
+        }
+    }
+
+    // acc_i + P_i * mat with P_i (R x C), mat (C x MC)
+    fn check_bitsliced_mat_mul_mat_add<const R: usize, const C: usize, const MC: usize>(
+        seed: u64,
+        upper_triangular: bool,
+    ) {
+        let p = random_matrices::<R, C>(seed, upper_triangular);
+        let mat = random_matrix::<C, MC>(seed + 1);
+        let mut acc = random_matrices::<R, MC>(seed + 2, false);
+
+        let bs_p = bitslice(&p, upper_triangular);
+        let mut bs_acc = bitslice(&acc, false);
+        bitsliced_mat_mul_mat_add!(bs_p, mat, &mut bs_acc, R, C, MC, upper_triangular);
+
+        add_products(&mut acc, |i| matrix_product(&p[i], &mat));
+        assert!(unbitslice::<R, MC>(&bs_acc, false) == acc);
+    }
+
+    // acc_i + mat^t * P_i with mat (MR x MC), P_i (MR x BC)
+    fn check_transposed_mat_mul_bitsliced_mat_add<
+        const MR: usize,
+        const MC: usize,
+        const BC: usize,
+    >(
+        seed: u64,
+    ) {
+        let mat = random_matrix::<MR, MC>(seed);
+        let p = random_matrices::<MR, BC>(seed + 1, false);
+        let mut acc = random_matrices::<MC, BC>(seed + 2, false);
+
+        let bs_p = bitslice(&p, false);
+        let mut bs_acc = bitslice(&acc, false);
+        transposed_mat_mul_bitsliced_mat_add!(mat, bs_p, &mut bs_acc, MR, MC, BC);
+
+        let mat_t = transpose(&mat);
+        add_products(&mut acc, |i| matrix_product(&mat_t, &p[i]));
+        assert!(unbitslice::<MC, BC>(&bs_acc, false) == acc);
+    }
+
+    // acc_i + P_i * mat^t with P_i (R x C), mat (MR x C)
+    fn check_bitsliced_mat_mul_transposed_mat_add<
+        const R: usize,
+        const C: usize,
+        const MR: usize,
+    >(
+        seed: u64,
+        upper_triangular: bool,
+    ) {
+        let p = random_matrices::<R, C>(seed, upper_triangular);
+        let mat = random_matrix::<MR, C>(seed + 1);
+        let mut acc = random_matrices::<R, MR>(seed + 2, false);
+
+        // The result is written after `offset` unrelated words of the accumulator
+        let offset = U32_PER_IDX * (seed as usize % 3);
+        let bs_p = bitslice(&p, upper_triangular);
+        let mut bs_acc = vec![0u32; offset];
+        bs_acc.extend(bitslice(&acc, false));
+        bitsliced_mat_mul_transposed_mat_add!(
+            bs_p,
+            mat,
+            &mut bs_acc,
+            R,
+            C,
+            MR,
+            offset,
+            upper_triangular
+        );
+
+        let mat_t = transpose(&mat);
+        add_products(&mut acc, |i| matrix_product(&p[i], &mat_t));
+        assert!(bs_acc[..offset].iter().all(|&word| word == 0));
+        assert!(unbitslice::<R, MR>(&bs_acc[offset..], false) == acc);
+    }
+
+    // acc_i + mat * P_i with mat (MR x MC), P_i (MC x BC)
+    fn check_mat_mul_bitsliced_mat_add<const MR: usize, const MC: usize, const BC: usize>(
+        seed: u64,
+    ) {
+        let mat = random_matrix::<MR, MC>(seed);
+        let p = random_matrices::<MC, BC>(seed + 1, false);
+        let mut acc = random_matrices::<MR, BC>(seed + 2, false);
+
+        let bs_p = bitslice(&p, false);
+        let mut bs_acc = bitslice(&acc, false);
+        mat_mul_bitsliced_mat_add!(mat, bs_p, &mut bs_acc, MR, MC, BC);
+
+        add_products(&mut acc, |i| matrix_product(&mat, &p[i]));
+        assert!(unbitslice::<MR, BC>(&bs_acc, false) == acc);
+    }
+
+    // Upper(P_i): P_i + P_i^t above the diagonal, the diagonal of P_i on it
+    fn check_upper<const R: usize>(seed: u64) {
+        let p = random_matrices::<R, R>(seed, false);
+
+        let bs_p = bitslice(&p, false);
+        let mut bs_upper = vec![0u32; R * (R + 1) / 2 * U32_PER_IDX];
+        upper!(bs_p, &mut bs_upper, R, R);
+
+        let mut expected = random_matrices::<R, R>(seed, true);
+        for (expected, p) in expected.iter_mut().zip(&p) {
+            for i in 0..R {
+                for j in i + 1..R {
+                    expected[i][j] = add(p[i][j], p[j][i]);
+                }
+            }
+        }
+        assert!(unbitslice::<R, R>(&bs_upper, true) == expected);
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(4))]
+
+        #[test]
+        fn test_bitsliced_mat_mul_mat_add(seed in any::<u64>()) {
+            check_bitsliced_mat_mul_mat_add::<V, V, O>(seed, true); // P1 * O
+            check_bitsliced_mat_mul_mat_add::<V, V, O>(seed, false); // (P1 + P1^t) * O
+            check_bitsliced_mat_mul_mat_add::<O, V, K>(seed, false);
+        }
+
+        #[test]
+        fn test_transposed_mat_mul_bitsliced_mat_add(seed in any::<u64>()) {
+            check_transposed_mat_mul_bitsliced_mat_add::<V, O, O>(seed); // O^t * (P1 * O + P2)
+            check_transposed_mat_mul_bitsliced_mat_add::<V, O, K>(seed);
+            check_transposed_mat_mul_bitsliced_mat_add::<K, O, V>(seed);
+        }
+
+        #[test]
+        fn test_bitsliced_mat_mul_transposed_mat_add(seed in any::<u64>()) {
+            check_bitsliced_mat_mul_transposed_mat_add::<V, V, K>(seed, true); // P1 * v^t
+            check_bitsliced_mat_mul_transposed_mat_add::<V, O, K>(seed, false); // P2 * s2^t
+            check_bitsliced_mat_mul_transposed_mat_add::<O, O, K>(seed, true); // P3 * s2^t
+        }
+
+        #[test]
+        fn test_mat_mul_bitsliced_mat_add(seed in any::<u64>()) {
+            check_mat_mul_bitsliced_mat_add::<K, V, O>(seed); // v * L
+            check_mat_mul_bitsliced_mat_add::<K, V, K>(seed); // v * (P1 * v^t)
+            check_mat_mul_bitsliced_mat_add::<K, N, K>(seed); // s * (P * s^t)
+        }
+
+        #[test]
+        fn test_upper(seed in any::<u64>()) {
+            check_upper::<K>(seed);
+            check_upper::<O>(seed);
+        }
+
+        #[test]
+        fn test_p1_add_p1t(seed in any::<u64>()) {
+            let p1 = random_matrices::<V, V>(seed, true);
+
+            let bs_p1 = bitslice(&p1, true);
+            let mut bs_p1_p1t = vec![0u32; V * V * U32_PER_IDX];
+            p1_add_p1t(&bs_p1, &mut bs_p1_p1t);
+
+            let mut expected = p1.clone();
+            add_products(&mut expected, |i| transpose(&p1[i]));
+            prop_assert!(unbitslice::<V, V>(&bs_p1_p1t, false) == expected);
+        }
+
+        // s^t P = (P * s^t) with P = [[P1, P2], [0, P3]], as N x K matrices
+        #[test]
+        fn test_calculate_st_p(seed in any::<u64>()) {
+            let p1 = random_matrices::<V, V>(seed, true);
+            let p2 = random_matrices::<V, O>(seed + 1, false);
+            let p3 = random_matrices::<O, O>(seed + 2, true);
+            let s = random_matrix::<K, N>(seed + 3);
+
+            let bs_p1: Box<[u32; P1_BYTES / 4]> = bitslice(&p1, true).try_into().unwrap();
+            let bs_p2: Box<[u32; P2_BYTES / 4]> = bitslice(&p2, false).try_into().unwrap();
+            let bs_p3: Box<[u32; P3_BYTES / 4]> = bitslice(&p3, true).try_into().unwrap();
+            let st_p = calculate_st_p(&bs_p1, &bs_p2, &bs_p3, s);
+
+            let s_t = transpose(&s);
+            let mut expected = vec![[[0u8; K]; N]; M];
+            for (i, expected) in expected.iter_mut().enumerate() {
+                let mut p = [[0u8; N]; N];
+                for r in 0..V {
+                    p[r][..V].copy_from_slice(&p1[i][r]);
+                    p[r][V..].copy_from_slice(&p2[i][r]);
+                }
+                for r in 0..O {
+                    p[V + r][V..].copy_from_slice(&p3[i][r]);
+                }
+                *expected = matrix_product(&p, &s_t);
+            }
+            prop_assert!(unbitslice::<N, K>(&st_p, false) == expected);
+        }
+    }
+}
@@ -218,3 +218,104 @@ macro_rules! decode_bit_sliced_matrices {
 
 
 
+
+#[cfg(test)]
+mod tests {
+    use crate::constants::{K, M, N, O, V};
+    use proptest::prelude::*;
+
+    fn elements(len: usize) -> impl Strategy<Value = Vec<u8>> {
+        prop::collection::vec(0u8..16, len)
+    }
+
+    // decode_bit_sliced_array! reads the bytes of encode_bit_sliced_array! as little-endian u32s
+    fn decode_bytes(bytes: &[u8]) -> [u8; M] {
+        let words: Vec<u32> = bytes
+            .chunks(4)
+            .map(|word| u32::from_le_bytes(word.try_into().unwrap()))
+            .collect();
+        decode_bit_sliced_array!(words)
+    }
+
+    // M matrices of size O x O, filled row by row
+    fn matrices(elements: &[u8]) -> [[[u8; O]; O]; M] {
+        let mut a = [[[0u8; O]; O]; M];
+        for (entry, &element) in a.iter_mut().flatten().flatten().zip(elements) {
+            *entry = element;
+        }
+        a
+    }
+
+    proptest! {
+        #[test]
+        fn test_bit_sliced_array_round_trip(v in elements(M)) {
+            let encoded = encode_bit_sliced_array!(v, M);
+            prop_assert_eq!(encoded.len(), M / 2);
+            prop_assert_eq!(&decode_bytes(&encoded)[..], &v[..]);
+        }
+
+        // Bit plane b holds bit b of every element, element i at bit i % 8 of byte i / 8
+        #[test]
+        fn test_bit_sliced_array_planes(v in elements(M)) {
+            let encoded = encode_bit_sliced_array!(v, M);
+            for (i, element) in v.iter().enumerate() {
+                for bit in 0..4 {
+                    let byte = encoded[bit * M / 8 + i / 8];
+                    prop_assert_eq!((byte >> (i % 8)) & 1, (element >> bit) & 1);
+                }
+            }
+        }
+
+        // The entries are encoded row by row, the upper triangular encoding skips those below
+        // the diagonal
+        #[test]
+        fn test_bit_sliced_matrices(a in elements(M * O * O)) {
+            let a = matrices(&a);
+            let full = encode_bit_sliced_matrices!(a, O, O, M, false, O * O * M / 2);
+            let upper = encode_bit_sliced_matrices!(a, O, O, M, true, O * (O + 1) / 2 * M / 2);
+
+            let mut upper_entries = upper.chunks(M / 2);
+            for (index, entry) in full.chunks(M / 2).enumerate() {
+                let (i, j) = (index / O, index % O);
+                let decoded = decode_bytes(entry);
+                for (matrix, element) in a.iter().zip(decoded) {
+                    prop_assert_eq!(matrix[i][j], element);
+                }
+                if i <= j {
+                    prop_assert_eq!(upper_entries.next(), Some(entry));
+                }
+            }
+            prop_assert_eq!(upper_entries.next(), None);
+        }
+
+        #[test]
+        fn test_bytestring_round_trip(v in elements(V), s in elements(K * N)) {
+            let encoded = encode_to_bytestring_array!(v, V, V.div_ceil(2));
+            prop_assert_eq!(&decode_bytestring_to_array!(encoded, V)[..], &v[..]);
+
+            let encoded = encode_to_bytestring_array!(s, K * N, (K * N).div_ceil(2));
+            prop_assert_eq!(&decode_bytestring_to_array!(encoded, K * N)[..], &s[..]);
+        }
+
+        // An odd number of elements leaves the high nibble of the last byte zero
+        #[test]
+        fn test_bytestring_odd_length(v in elements(2 * V + 1)) {
+            const LEN: usize = 2 * V + 1;
+            let encoded = encode_to_bytestring_array!(v, LEN, LEN / 2 + 1);
+            prop_assert_eq!(encoded[LEN / 2], v[LEN - 1]);
+            for (i, byte) in encoded[..LEN / 2].iter().enumerate() {
+                prop_assert_eq!(*byte, v[2 * i] | v[2 * i + 1] << 4);
+            }
+            prop_assert_eq!(&decode_bytestring_to_array!(encoded, LEN)[..], &v[..]);
+        }
+
+        #[test]
+        fn test_bytestring_matrix(bytes in prop::collection::vec(any::<u8>(), V * O / 2 + 1)) {
+            let matrix = decode_bytestring_matrix_array!(bytes, V, O);
+            let elements = decode_bytestring_to_array!(bytes, V * O);
+            for (row, chunk) in matrix.iter().zip(elements.chunks(O)) {
+                prop_assert_eq!(&row[..], chunk);
+            }
+        }
+    }
+}
@@ -0,0 +1,459 @@
+// Command line interface for key generation, signing and verification:
+//
+//   mayo keygen --level <1|2|3|5> --out <name> [--format hex|raw|base64]
+//   mayo sign --key <file.sk> [--out <file.sig>] [--format hex|raw|base64] <file>
+//   mayo verify --pk <file.pk> --sig <file.sig> <file>
+//   mayo pubkey [--out <file.pk>] [--format hex|raw|base64] <file.sk>
+//   mayo acvp [--out <response.json>] [--expected <expectedResults.json>] <prompt.json>
+//
+// The parameter set is fixed at compile time by the mayoN feature. Keys and signatures are
+// read in any of the three formats, a message file of "-" is read from stdin. The acvp command
+// (feature `acvp`) answers an ACVP JSON prompt, see `lib::acvp`. The module and its dependencies
+// (base64, getrandom) are only built with the `cli` feature.
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+
+use crate::constants::{CPK_BYTES, CSK_BYTES, DIGEST_BYTES, SIG_BYTES, VERSION};
+use crate::crypto_primitives::safe_random_bytes_init;
+use crate::mayo_functionality::{
+    compact_key_gen, compact_key_gen_from_seed, expand_pk, sign_digest, verify_digest,
+    CompactPublicKey,
+};
+use crate::message_hasher::MessageHasher;
+use crate::utils::{bytes_to_hex_string, hex_string_to_bytes};
+
+// Exit codes
+pub const EXIT_OK: i32 = 0; // Success, or a valid signature for verify
+pub const EXIT_INVALID_SIGNATURE: i32 = 1; // Or ACVP results that differ from --expected
+pub const EXIT_USAGE: i32 = 2; // Bad arguments or unsupported level
+pub const EXIT_IO: i32 = 3; // A file could not be read or written
+pub const EXIT_MALFORMED: i32 = 4; // A key or signature could not be decoded
+
+const USAGE: &str = "Usage:
+  mayo keygen --level <1|2|3|5> --out <name> [--format hex|raw|base64]
+  mayo sign --key <file.sk> [--out <file.sig>] [--format hex|raw|base64] <file>
+  mayo verify --pk <file.pk> --sig <file.sig> <file>
+  mayo pubkey [--out <file.pk>] [--format hex|raw|base64] <file.sk>
+  mayo acvp [--out <response.json>] [--expected <expectedResults.json>] <prompt.json>
+    (acvp needs the acvp feature)";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Hex,
+    Raw,
+    Base64,
+}
+
+#[derive(Debug)]
+pub struct CliError {
+    pub exit_code: i32,
+    pub message: String,
+}
+
+impl CliError {
+    fn usage(message: impl Into<String>) -> Self {
+        CliError {
+            exit_code: EXIT_USAGE,
+            message: format!("{}\n\n{}", message.into(), USAGE),
+        }
+    }
+
+    fn io(path: &str, error: io::Error) -> Self {
+        CliError {
+            exit_code: EXIT_IO,
+            message: format!("{}: {}", path, error),
+        }
+    }
+
+    fn malformed(message: impl Into<String>) -> Self {
+        CliError {
+            exit_code: EXIT_MALFORMED,
+            message: message.into(),
+        }
+    }
+}
+
+// Runs the command given by `args` (without the program name) and returns the exit code
+pub fn run(args: &[String]) -> i32 {
+    match run_command(args) {
+        Ok(exit_code) => exit_code,
+        Err(error) => {
+            eprintln!("mayo: {}", error.message);
+            error.exit_code
+        }
+    }
+}
+
+fn run_command(args: &[String]) -> Result<i32, CliError> {
+    let (command, rest) = args
+        .split_first()
+        .ok_or_else(|| CliError::usage("No command given"))?;
+    let options = Options::parse(rest)?;
+
+    match command.as_str() {
+        "keygen" => keygen(&options),
+        "sign" => sign_file(&options),
+        "verify" => verify_file(&options),
+        "pubkey" => pubkey(&options),
+        #[cfg(feature = "acvp")]
+        "acvp" => acvp_file(&options),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(EXIT_OK)
+        }
+        _ => Err(CliError::usage(format!("Unknown command '{}'", command))),
+    }
+}
+
+// Parsed `--name value` options and positional arguments
+#[derive(Default)]
+struct Options {
+    level: Option<String>,
+    out: Option<String>,
+    format: Option<Format>,
+    key: Option<String>,
+    pk: Option<String>,
+    sig: Option<String>,
+    expected: Option<String>,
+    positional: Vec<String>,
+}
+
+impl Options {
+    fn parse(args: &[String]) -> Result<Self, CliError> {
+        let mut options = Options::default();
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            let slot = match arg.as_str() {
+                "--level" => &mut options.level,
+                "--out" => &mut options.out,
+                "--key" => &mut options.key,
+                "--pk" => &mut options.pk,
+                "--sig" => &mut options.sig,
+                "--expected" => &mut options.expected,
+                "--format" => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| CliError::usage("--format needs a value"))?;
+                    options.format = Some(parse_format(value)?);
+                    continue;
+                }
+                _ if arg.starts_with("--") => {
+                    return Err(CliError::usage(format!("Unknown option '{}'", arg)));
+                }
+                _ => {
+                    options.positional.push(arg.clone());
+                    continue;
+                }
+            };
+
+            let value = args
+                .next()
+                .ok_or_else(|| CliError::usage(format!("{} needs a value", arg)))?;
+            *slot = Some(value.clone());
+        }
+        Ok(options)
+    }
+
+    fn required<'a>(value: &'a Option<String>, name: &str) -> Result<&'a str, CliError> {
+        value
+            .as_deref()
+            .ok_or_else(|| CliError::usage(format!("Missing {}", name)))
+    }
+
+    // The single positional argument of the command
+    fn input(&self, name: &str) -> Result<&str, CliError> {
+        match self.positional.as_slice() {
+            [input] => Ok(input),
+            [] => Err(CliError::usage(format!("Missing {}", name))),
+            _ => Err(CliError::usage("Too many arguments")),
+        }
+    }
+
+    fn format(&self) -> Format {
+        self.format.unwrap_or(Format::Hex)
+    }
+}
+
+fn parse_format(value: &str) -> Result<Format, CliError> {
+    match value {
+        "hex" => Ok(Format::Hex),
+        "raw" => Ok(Format::Raw),
+        "base64" => Ok(Format::Base64),
+        _ => Err(CliError::usage(format!("Unknown format '{}'", value))),
+    }
+}
+
+// Level of the compiled parameter set, e.g. "1" for MAYO_1
+fn compiled_level() -> &'static str {
+    VERSION.trim_start_matches("MAYO_")
+}
+
+// Accepts the level of the compiled parameter set, other MAYO levels need a rebuild
+fn check_level(level: &str) -> Result<(), CliError> {
+    if !["1", "2", "3", "5"].contains(&level) {
+        return Err(CliError::usage(format!(
+            "Unknown level '{}', expected 1, 2, 3 or 5",
+            level
+        )));
+    }
+    if level != compiled_level() {
+        return Err(CliError::usage(format!(
+            "This build only supports level {}, rebuild with --features mayo{} for level {}",
+            compiled_level(),
+            level,
+            level
+        )));
+    }
+    Ok(())
+}
+
+fn keygen(options: &Options) -> Result<i32, CliError> {
+    let level = Options::required(&options.level, "--level")?;
+    check_level(level)?;
+    let out = Options::required(&options.out, "--out")?;
+    if !options.positional.is_empty() {
+        return Err(CliError::usage("Too many arguments"));
+    }
+
+    seed_randomness()?;
+    let (cpk, csk) = compact_key_gen();
+
+    write_secret(&format!("{}.sk", out), &encode(&csk, options.format()))?;
+    write_output(
+        Some(&format!("{}.pk", out)),
+        &encode(&cpk.to_bytes(), options.format()),
+    )?;
+    Ok(EXIT_OK)
+}
+
+fn sign_file(options: &Options) -> Result<i32, CliError> {
+    let key_path = Options::required(&options.key, "--key")?;
+    let message_path = options.input("message file")?;
+
+    let csk = read_secret_key(key_path)?;
+    let m_digest = digest_file(message_path)?;
+
+    seed_randomness()?;
+    let signature = sign_digest(csk, &m_digest);
+
+    write_output(
+        options.out.as_deref(),
+        &encode(&signature, options.format()),
+    )?;
+    Ok(EXIT_OK)
+}
+
+fn verify_file(options: &Options) -> Result<i32, CliError> {
+    let pk_path = Options::required(&options.pk, "--pk")?;
+    let sig_path = Options::required(&options.sig, "--sig")?;
+    let message_path = options.input("message file")?;
+
+    let cpk_bytes = read_encoded(pk_path, CPK_BYTES)?;
+    let cpk = CompactPublicKey::from_bytes(&cpk_bytes).map_err(CliError::malformed)?;
+    let signature = read_encoded(sig_path, SIG_BYTES)?;
+    let m_digest = digest_file(message_path)?;
+
+    if verify_digest(expand_pk(cpk), &signature, &m_digest) {
+        println!("Signature is valid");
+        Ok(EXIT_OK)
+    } else {
+        println!("Signature is INVALID");
+        Ok(EXIT_INVALID_SIGNATURE)
+    }
+}
+
+fn pubkey(options: &Options) -> Result<i32, CliError> {
+    let key_path = options.input("secret key file")?;
+
+    let csk = read_secret_key(key_path)?;
+    let (cpk, _) = compact_key_gen_from_seed(csk);
+
+    write_output(
+        options.out.as_deref(),
+        &encode(&cpk.to_bytes(), options.format()),
+    )?;
+    Ok(EXIT_OK)
+}
+
+// Answers an ACVP prompt and compares the response with the expected results, if given
+#[cfg(feature = "acvp")]
+fn acvp_file(options: &Options) -> Result<i32, CliError> {
+    use crate::acvp::{compare, parse_acvp, respond};
+
+    let read_json = |path: &str| {
+        let contents = fs::read_to_string(path).map_err(|error| CliError::io(path, error))?;
+        parse_acvp(&contents).map_err(|error| CliError::malformed(format!("{}: {}", path, error)))
+    };
+    let prompt_path = options.input("prompt file")?;
+    let response = respond(&read_json(prompt_path)?)
+        .map_err(|error| CliError::malformed(format!("{}: {}", prompt_path, error)))?;
+
+    let mut json = serde_json::to_string_pretty(&response).expect("Serializing JSON values");
+    json.push('\n');
+    write_output(options.out.as_deref(), json.as_bytes())?;
+
+    let Some(expected_path) = options.expected.as_deref() else {
+        return Ok(EXIT_OK);
+    };
+    let mismatches = compare(&read_json(expected_path)?, &response)
+        .map_err(|error| CliError::malformed(format!("{}: {}", expected_path, error)))?;
+    for mismatch in &mismatches {
+        eprintln!("{}", mismatch);
+    }
+    if mismatches.is_empty() {
+        eprintln!("All results match {}", expected_path);
+        Ok(EXIT_OK)
+    } else {
+        Ok(EXIT_INVALID_SIGNATURE)
+    }
+}
+
+// Seeds the randomness source used by keygen and sign from the operating system.
+// Without this the NIST randomness source would produce the same keys on every run.
+fn seed_randomness() -> Result<(), CliError> {
+    let mut entropy_input = [0u8; 48];
+    getrandom::getrandom(&mut entropy_input).map_err(|error| CliError {
+        exit_code: EXIT_IO,
+        message: format!(
+            "Could not get randomness from the operating system: {}",
+            error
+        ),
+    })?;
+    safe_random_bytes_init(&mut entropy_input, &[0u8; 48], 256);
+    Ok(())
+}
+
+// Digest of a message file, read in chunks. "-" reads from stdin
+fn digest_file(path: &str) -> Result<[u8; DIGEST_BYTES], CliError> {
+    let mut hasher = MessageHasher::new();
+    let result = if path == "-" {
+        hasher.update_from_reader(&mut io::stdin().lock())
+    } else {
+        File::open(path).and_then(|mut file| hasher.update_from_reader(&mut file))
+    };
+    result.map_err(|error| CliError::io(path, error))?;
+    Ok(hasher.finalize())
+}
+
+fn read_secret_key(path: &str) -> Result<[u8; CSK_BYTES], CliError> {
+    let bytes = read_encoded(path, CSK_BYTES)?;
+    Ok(bytes.try_into().expect("Length checked by read_encoded"))
+}
+
+// Reads a key or signature of `expected_len` bytes stored as raw bytes, hex or base64
+fn read_encoded(path: &str, expected_len: usize) -> Result<Vec<u8>, CliError> {
+    let mut contents = Vec::new();
+    File::open(path)
+        .and_then(|mut file| file.read_to_end(&mut contents))
+        .map_err(|error| CliError::io(path, error))?;
+
+    decode(&contents, expected_len).ok_or_else(|| {
+        CliError::malformed(format!(
+            "{}: expected {} bytes as raw bytes, hex or base64 ({})",
+            path, expected_len, VERSION
+        ))
+    })
+}
+
+pub fn encode(bytes: &[u8], format: Format) -> Vec<u8> {
+    match format {
+        Format::Raw => bytes.to_vec(),
+        Format::Hex => format!("{}\n", bytes_to_hex_string(bytes, false)).into_bytes(),
+        Format::Base64 => format!("{}\n", BASE64.encode(bytes)).into_bytes(),
+    }
+}
+
+// Decodes raw bytes, hex or base64, whichever gives `expected_len` bytes
+pub fn decode(contents: &[u8], expected_len: usize) -> Option<Vec<u8>> {
+    if contents.len() == expected_len {
+        return Some(contents.to_vec());
+    }
+
+    let text = std::str::from_utf8(contents).ok()?.trim();
+    if text.len() == 2 * expected_len && text.bytes().all(|c| c.is_ascii_hexdigit()) {
+        return Some(hex_string_to_bytes(text));
+    }
+
+    BASE64
+        .decode(text)
+        .ok()
+        .filter(|bytes| bytes.len() == expected_len)
+}
+
+// Writes to `path`, or to stdout if no path is given
+fn write_output(path: Option<&str>, contents: &[u8]) -> Result<(), CliError> {
+    match path {
+        Some(path) => fs::write(path, contents).map_err(|error| CliError::io(path, error)),
+        None => io::stdout()
+            .write_all(contents)
+            .map_err(|error| CliError::io("stdout", error)),
+    }
+}
+
+// Writes a secret key file that is only readable by the owner.
+// The mode given to open only applies to a new file, so an existing file is restricted before
+// the key is written to it.
+fn write_secret(path: &str, contents: &[u8]) -> Result<(), CliError> {
+    let mut file_options = fs::OpenOptions::new();
+    file_options.write(true).create(true).truncate(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        file_options.mode(0o600);
+    }
+
+    file_options
+        .open(path)
+        .and_then(|mut file| {
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                file.set_permissions(fs::Permissions::from_mode(0o600))?;
+            }
+            file.write_all(contents)
+        })
+        .map_err(|error| CliError::io(path, error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode() {
+        let bytes: Vec<u8> = (0..SIG_BYTES as u32).map(|i| (i * 7) as u8).collect();
+
+        for format in [Format::Hex, Format::Raw, Format::Base64] {
+            let encoded = encode(&bytes, format);
+            assert_eq!(decode(&encoded, SIG_BYTES), Some(bytes.clone()));
+        }
+
+        assert_eq!(decode(&bytes[1..], SIG_BYTES), None);
+        assert_eq!(decode(b"not a signature", SIG_BYTES), None);
+    }
+
+    #[test]
+    fn test_check_level() {
+        assert!(check_level(compiled_level()).is_ok());
+
+        let unknown = check_level("4").unwrap_err();
+        assert_eq!(unknown.exit_code, EXIT_USAGE);
+        assert!(unknown.message.starts_with("Unknown level '4'"));
+        assert!(!unknown.message.contains("mayo4"));
+
+        let other = ["1", "2", "3", "5"]
+            .into_iter()
+            .find(|level| *level != compiled_level())
+            .unwrap();
+        let rebuild = check_level(other).unwrap_err();
+        assert_eq!(rebuild.exit_code, EXIT_USAGE);
+        assert!(rebuild
+            .message
+            .contains(&format!("rebuild with --features mayo{}", other)));
+    }
+}
@@ -0,0 +1,274 @@
+// Composite MAYO + Ed25519 signatures, following the structure of the IETF composite signatures
+// draft (draft-ietf-lamps-pq-composite-sigs). Both algorithms sign the message representative
+//
+//   M' = Prefix || Label || len(ctx) || ctx || SHA-512(M)
+//
+// where MAYO additionally binds the Label as its context string (see `sign_with_context`).
+// A composite signature is only valid if both component signatures are valid.
+//
+// Keys and signatures are the fixed-length concatenation of the MAYO and Ed25519 encodings:
+//   public key: MAYO compact public key || Ed25519 public key
+//   secret key: MAYO compact secret key || Ed25519 secret key (seed)
+//   signature:  MAYO signature || Ed25519 signature
+use ed25519_dalek::{Signer, SECRET_KEY_LENGTH, SIGNATURE_LENGTH};
+use sha2::{Digest, Sha512};
+
+use crate::constants::{CPK_BYTES, CSK_BYTES, SIG_BYTES};
+use crate::crypto_primitives::safe_random_bytes;
+use crate::mayo_functionality::{
+    compact_key_gen, compact_key_gen_from_seed, expand_pk, sign_with_context, verify_with_context,
+    CompactPublicKey,
+};
+use crate::message_hasher::MAX_CONTEXT_BYTES;
+
+pub const ED25519_PK_BYTES: usize = ed25519_dalek::PUBLIC_KEY_LENGTH;
+pub const COMPOSITE_PK_BYTES: usize = CPK_BYTES + ED25519_PK_BYTES;
+pub const COMPOSITE_SK_BYTES: usize = CSK_BYTES + SECRET_KEY_LENGTH;
+pub const COMPOSITE_SIG_BYTES: usize = SIG_BYTES + SIGNATURE_LENGTH;
+
+// "CompositeAlgorithmSignatures2025" as in the draft
+const PREFIX: &[u8] = b"CompositeAlgorithmSignatures2025";
+
+// Domain separation label of the composite algorithm for the compiled parameter set
+#[cfg(feature = "mayo1")]
+const LABEL: &[u8] = b"COMPSIG-MAYO1-Ed25519-SHA512";
+#[cfg(feature = "mayo2")]
+const LABEL: &[u8] = b"COMPSIG-MAYO2-Ed25519-SHA512";
+#[cfg(feature = "mayo3")]
+const LABEL: &[u8] = b"COMPSIG-MAYO3-Ed25519-SHA512";
+#[cfg(feature = "mayo5")]
+const LABEL: &[u8] = b"COMPSIG-MAYO5-Ed25519-SHA512";
+
+#[derive(Clone)]
+pub struct CompositeSigningKey {
+    mayo_csk: [u8; CSK_BYTES],
+    ed25519: ed25519_dalek::SigningKey,
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct CompositeVerifyingKey {
+    mayo_cpk: CompactPublicKey,
+    ed25519: ed25519_dalek::VerifyingKey,
+}
+
+// Generates a composite key pair using the NIST randomness source for both components
+pub fn composite_key_gen() -> (CompositeVerifyingKey, CompositeSigningKey) {
+    let (mayo_cpk, mayo_csk) = compact_key_gen();
+
+    let mut ed25519_seed = [0u8; SECRET_KEY_LENGTH];
+    safe_random_bytes(&mut ed25519_seed, SECRET_KEY_LENGTH as u64);
+    let ed25519 = ed25519_dalek::SigningKey::from_bytes(&ed25519_seed);
+
+    let verifying_key = CompositeVerifyingKey {
+        mayo_cpk,
+        ed25519: ed25519.verifying_key(),
+    };
+    (verifying_key, CompositeSigningKey { mayo_csk, ed25519 })
+}
+
+impl CompositeSigningKey {
+    // Signs `message` with both components. `context` is at most 255 bytes and may be empty.
+    pub fn sign(
+        &self,
+        message: &[u8],
+        context: &[u8],
+    ) -> Result<[u8; COMPOSITE_SIG_BYTES], &'static str> {
+        let m_prime = message_representative(message, context)?;
+
+        let mayo_signature = sign_with_context(self.mayo_csk, &m_prime, LABEL)?;
+        let ed25519_signature = self.ed25519.sign(&m_prime);
+
+        let mut signature = [0u8; COMPOSITE_SIG_BYTES];
+        signature[..SIG_BYTES].copy_from_slice(&mayo_signature);
+        signature[SIG_BYTES..].copy_from_slice(&ed25519_signature.to_bytes());
+        Ok(signature)
+    }
+
+    pub fn verifying_key(&self) -> CompositeVerifyingKey {
+        let (mayo_cpk, _) = compact_key_gen_from_seed(self.mayo_csk);
+        CompositeVerifyingKey {
+            mayo_cpk,
+            ed25519: self.ed25519.verifying_key(),
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; COMPOSITE_SK_BYTES] {
+        let mut bytes = [0u8; COMPOSITE_SK_BYTES];
+        bytes[..CSK_BYTES].copy_from_slice(&self.mayo_csk);
+        bytes[CSK_BYTES..].copy_from_slice(self.ed25519.as_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, &'static str> {
+        if bytes.len() != COMPOSITE_SK_BYTES {
+            return Err("The composite secret key has incorrect length");
+        }
+        let (mayo_csk, ed25519_seed) = bytes.split_at(CSK_BYTES);
+
+        Ok(CompositeSigningKey {
+            mayo_csk: mayo_csk.try_into().expect("Slice has incorrect length"),
+            ed25519: ed25519_dalek::SigningKey::from_bytes(
+                ed25519_seed.try_into().expect("Slice has incorrect length"),
+            ),
+        })
+    }
+}
+
+impl CompositeVerifyingKey {
+    // Returns true only if both the MAYO and the Ed25519 signature are valid
+    pub fn verify(&self, message: &[u8], context: &[u8], signature: &[u8]) -> bool {
+        if signature.len() != COMPOSITE_SIG_BYTES {
+            return false;
+        }
+        let m_prime = match message_representative(message, context) {
+            Ok(m_prime) => m_prime,
+            Err(_) => return false,
+        };
+        let (mayo_signature, ed25519_signature) = signature.split_at(SIG_BYTES);
+
+        let ed25519_signature = ed25519_dalek::Signature::from_bytes(
+            ed25519_signature
+                .try_into()
+                .expect("Slice has incorrect length"),
+        );
+        let ed25519_valid = self
+            .ed25519
+            .verify_strict(&m_prime, &ed25519_signature)
+            .is_ok();
+
+        let mayo_valid = verify_with_context(
+            expand_pk(self.mayo_cpk.clone()),
+            mayo_signature,
+            &m_prime,
+            LABEL,
+        );
+
+        mayo_valid && ed25519_valid
+    }
+
+    pub fn to_bytes(&self) -> [u8; COMPOSITE_PK_BYTES] {
+        let mut bytes = [0u8; COMPOSITE_PK_BYTES];
+        bytes[..CPK_BYTES].copy_from_slice(&self.mayo_cpk.to_bytes());
+        bytes[CPK_BYTES..].copy_from_slice(self.ed25519.as_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, &'static str> {
+        if bytes.len() != COMPOSITE_PK_BYTES {
+            return Err("The composite public key has incorrect length");
+        }
+        let (mayo_cpk, ed25519_pk) = bytes.split_at(CPK_BYTES);
+
+        Ok(CompositeVerifyingKey {
+            mayo_cpk: CompactPublicKey::from_bytes(mayo_cpk)?,
+            ed25519: ed25519_dalek::VerifyingKey::from_bytes(
+                ed25519_pk.try_into().expect("Slice has incorrect length"),
+            )
+            .map_err(|_| "The Ed25519 public key is invalid")?,
+        })
+    }
+}
+
+// M' = Prefix || Label || len(ctx) || ctx || SHA-512(M)
+fn message_representative(message: &[u8], context: &[u8]) -> Result<Vec<u8>, &'static str> {
+    if context.len() > MAX_CONTEXT_BYTES {
+        return Err("The context string is longer than 255 bytes");
+    }
+
+    let mut m_prime = Vec::with_capacity(PREFIX.len() + LABEL.len() + 1 + context.len() + 64);
+    m_prime.extend_from_slice(PREFIX);
+    m_prime.extend_from_slice(LABEL);
+    m_prime.push(context.len() as u8);
+    m_prime.extend_from_slice(context);
+    m_prime.extend_from_slice(&Sha512::digest(message));
+    Ok(m_prime)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let (verifying_key, signing_key) = composite_key_gen();
+
+        let signature = signing_key.sign(b"message", b"").unwrap();
+        assert!(verifying_key.verify(b"message", b"", &signature));
+        assert!(!verifying_key.verify(b"other message", b"", &signature));
+
+        let signature = signing_key.sign(b"message", b"context").unwrap();
+        assert!(verifying_key.verify(b"message", b"context", &signature));
+        assert!(!verifying_key.verify(b"message", b"", &signature));
+
+        assert!(signing_key.sign(b"message", &[0u8; 256]).is_err());
+        assert!(!verifying_key.verify(b"message", &[0u8; 256], &signature));
+    }
+
+    #[test]
+    fn test_one_half_invalid() {
+        let (verifying_key, signing_key) = composite_key_gen();
+        let (other_verifying_key, other_signing_key) = composite_key_gen();
+
+        let signature = signing_key.sign(b"message", b"").unwrap();
+        let other_message_signature = signing_key.sign(b"other message", b"").unwrap();
+        let other_key_signature = other_signing_key.sign(b"message", b"").unwrap();
+
+        // Splices the MAYO half of `mayo` with the Ed25519 half of `ed25519`
+        let combine =
+            |mayo: &[u8], ed25519: &[u8]| [&mayo[..SIG_BYTES], &ed25519[SIG_BYTES..]].concat();
+
+        // Tampered MAYO half, tampered Ed25519 half
+        let mut tampered = signature;
+        tampered[0] ^= 1;
+        assert!(!verifying_key.verify(b"message", b"", &tampered));
+        let mut tampered = signature;
+        tampered[SIG_BYTES] ^= 1;
+        assert!(!verifying_key.verify(b"message", b"", &tampered));
+
+        // One half valid for a different message
+        let spliced = combine(&other_message_signature, &signature);
+        assert!(!verifying_key.verify(b"message", b"", &spliced));
+        let spliced = combine(&signature, &other_message_signature);
+        assert!(!verifying_key.verify(b"message", b"", &spliced));
+
+        // One half from a different key
+        let spliced = combine(&other_key_signature, &signature);
+        assert!(!verifying_key.verify(b"message", b"", &spliced));
+        let spliced = combine(&signature, &other_key_signature);
+        assert!(!verifying_key.verify(b"message", b"", &spliced));
+
+        // One half of the verifying key from a different key pair
+        let mut mixed_key = verifying_key.clone();
+        mixed_key.ed25519 = other_verifying_key.ed25519;
+        assert!(!mixed_key.verify(b"message", b"", &signature));
+        let mut mixed_key = verifying_key.clone();
+        mixed_key.mayo_cpk = other_verifying_key.mayo_cpk.clone();
+        assert!(!mixed_key.verify(b"message", b"", &signature));
+
+        // A plain MAYO signature of the message is not a valid MAYO half
+        let plain_mayo = crate::mayo_functionality::sign(signing_key.mayo_csk, b"message");
+        let spliced = combine(&plain_mayo, &signature);
+        assert!(!verifying_key.verify(b"message", b"", &spliced));
+
+        // Truncated signature
+        assert!(!verifying_key.verify(b"message", b"", &signature[..COMPOSITE_SIG_BYTES - 1]));
+    }
+
+    #[test]
+    fn test_key_serialization() {
+        let (verifying_key, signing_key) = composite_key_gen();
+
+        let restored_signing_key =
+            CompositeSigningKey::from_bytes(&signing_key.to_bytes()).unwrap();
+        let restored_verifying_key =
+            CompositeVerifyingKey::from_bytes(&verifying_key.to_bytes()).unwrap();
+        assert!(restored_verifying_key == verifying_key);
+        assert!(restored_signing_key.verifying_key() == verifying_key);
+
+        let signature = restored_signing_key.sign(b"message", b"").unwrap();
+        assert!(restored_verifying_key.verify(b"message", b"", &signature));
+
+        assert!(CompositeSigningKey::from_bytes(&[0u8; COMPOSITE_SK_BYTES - 1]).is_err());
+        assert!(CompositeVerifyingKey::from_bytes(&[0u8; COMPOSITE_PK_BYTES + 1]).is_err());
+    }
+}
@@ -1,6 +1,7 @@
 #[cfg(feature = "mayo1")]
 mod mayo1_features {
     pub const VERSION: &str = "MAYO_1";
+    pub const OID: &str = "1.3.9999.8.1.3"; // Provisional (Open Quantum Safe), see key_encoding
     pub const N: usize = 66;
     pub const M: usize = 64;
     pub const O: usize = 8;
@@ -37,6 +38,7 @@ mod mayo1_features {
 mod mayo2_features {
 
     pub const VERSION: &str = "MAYO_2";
+    pub const OID: &str = "1.3.9999.8.2.3"; // Provisional (Open Quantum Safe), see key_encoding
     pub const N: usize = 78;
     pub const M: usize = 64;
     pub const O: usize = 18;
@@ -73,6 +75,7 @@ mod mayo2_features {
 mod mayo3_features {
 
     pub const VERSION: &str = "MAYO_3";
+    pub const OID: &str = "1.3.9999.8.3.3"; // Provisional (Open Quantum Safe), see key_encoding
     pub const N: usize = 99;
     pub const M: usize = 96;
     pub const O: usize = 10;
@@ -109,6 +112,7 @@ mod mayo3_features {
 mod mayo5_features {
 
     pub const VERSION: &str = "MAYO_5";
+    pub const OID: &str = "1.3.9999.8.5.3"; // Provisional (Open Quantum Safe), see key_encoding
     pub const N: usize = 133;
     pub const M: usize = 128;
     pub const O: usize = 12;
@@ -1,12 +1,23 @@
+// The functions below call the C sources in src/genkat and src/arm_neon_intrinsic. Targets without
+// a C toolchain (wasm32) and the `portable` feature use the Rust implementations in portable.rs,
+// which have the same signatures and produce the same output.
+#[cfg(not(mayo_portable))]
 use crate::arm_neon_intrinsic::arm_intrinsic::{
     mul_add_bitsliced_m_vec_mayo12, mul_add_bitsliced_m_vec_mayo3, mul_add_bitsliced_m_vec_mayo5,
 };
+#[cfg(not(mayo_portable))]
 use crate::genkat::bindings;
+#[cfg(not(mayo_portable))]
+pub use crate::genkat::bindings::shake256incctx as Shake256State;
+
+#[cfg(mayo_portable)]
+pub use crate::portable::*;
 
 /* RANDOMNESS AND EXTENDED OUTPUT FUNCTION USE NIST CALLS
     - SAME AS MAYO VERSION BY THE AUTHORS
 */
 
+#[cfg(not(mayo_portable))]
 pub fn safe_random_bytes_init(
     entropy_input: &mut [u8],
     personalization_string: &[u8],
@@ -21,18 +32,40 @@ pub fn safe_random_bytes_init(
     }
 }
 
+#[cfg(not(mayo_portable))]
 pub fn safe_random_bytes(random_arrays: &mut [u8], nbytes: u64) {
     unsafe {
         bindings::randombytes(random_arrays.as_mut_ptr(), nbytes);
     }
 }
 
+#[cfg(not(mayo_portable))]
 pub fn safe_aes_128_ctr(output: &mut [u32], output_byte_len: u64, input: &[u8]) {
     unsafe {
         bindings::AES_128_CTR(output.as_mut_ptr(), output_byte_len, input.as_ptr());
     }
 }
 
+// AES-128-CTR keystream starting `output_byte_offset` bytes into the stream (must be a multiple of the 16 byte block size)
+#[cfg(not(mayo_portable))]
+pub fn safe_aes_128_ctr_offset(
+    output: &mut [u32],
+    output_byte_offset: u64,
+    output_byte_len: u64,
+    input: &[u8],
+) {
+    debug_assert!(output_byte_offset.is_multiple_of(16));
+    unsafe {
+        bindings::AES_128_CTR_OFFSET(
+            output.as_mut_ptr(),
+            output_byte_len,
+            input.as_ptr(),
+            (output_byte_offset / 16) as u32,
+        );
+    }
+}
+
+#[cfg(not(mayo_portable))]
 pub fn safe_shake256(output: &mut [u8], output_byte_len: u64, input: &[u8], input_byte_len: u64) {
     unsafe {
         bindings::shake256(
@@ -44,6 +77,67 @@ pub fn safe_shake256(output: &mut [u8], output_byte_len: u64, input: &[u8], inpu
     }
 }
 
+#[cfg(not(mayo_portable))]
+pub fn safe_shake256_inc_init() -> Shake256State {
+    let mut state = Shake256State {
+        ctx: std::ptr::null_mut(),
+    };
+    unsafe {
+        bindings::shake256_inc_init(&mut state);
+    }
+    state
+}
+
+#[cfg(not(mayo_portable))]
+pub fn safe_shake256_inc_absorb(state: &mut Shake256State, input: &[u8]) {
+    unsafe {
+        bindings::shake256_inc_absorb(state, input.as_ptr(), input.len());
+    }
+}
+
+#[cfg(not(mayo_portable))]
+pub fn safe_shake256_inc_finalize(state: &mut Shake256State) {
+    unsafe {
+        bindings::shake256_inc_finalize(state);
+    }
+}
+
+#[cfg(not(mayo_portable))]
+pub fn safe_shake256_inc_squeeze(output: &mut [u8], state: &mut Shake256State) {
+    unsafe {
+        bindings::shake256_inc_squeeze(output.as_mut_ptr(), output.len(), state);
+    }
+}
+
+#[cfg(not(mayo_portable))]
+pub fn safe_shake256_inc_ctx_release(state: &mut Shake256State) {
+    unsafe {
+        bindings::shake256_inc_ctx_release(state);
+    }
+}
+
+// Incremental cSHAKE256 with the given customization string (NIST SP 800-185), a different
+// function from SHAKE256 for every customization. Absorb, squeeze and release with the SHAKE256
+// functions above.
+#[cfg(not(mayo_portable))]
+pub fn safe_cshake256_inc_init(customization: &[u8]) -> Shake256State {
+    let mut state = Shake256State {
+        ctx: std::ptr::null_mut(),
+    };
+    unsafe {
+        bindings::cshake256_inc_init(&mut state, customization.as_ptr(), customization.len());
+    }
+    state
+}
+
+#[cfg(not(mayo_portable))]
+pub fn safe_cshake256_inc_finalize(state: &mut Shake256State) {
+    unsafe {
+        bindings::cshake256_inc_finalize(state);
+    }
+}
+
+#[cfg(not(mayo_portable))]
 pub fn safe_mul_add_bitsliced_m_vec_mayo12(
     input: &[u32],
     input_start: i32,
@@ -62,6 +156,7 @@ pub fn safe_mul_add_bitsliced_m_vec_mayo12(
     }
 }
 
+#[cfg(not(mayo_portable))]
 pub fn safe_mul_add_bitsliced_m_vec_mayo3(
     input: &[u32],
     input_start: i32,
@@ -80,6 +175,7 @@ pub fn safe_mul_add_bitsliced_m_vec_mayo3(
     }
 }
 
+#[cfg(not(mayo_portable))]
 pub fn safe_mul_add_bitsliced_m_vec_mayo5(
     input: &[u32],
     input_start: i32,
@@ -0,0 +1,351 @@
+// Elements of the extension field GF(16)[z]/f(z) with the irreducible f(z) = z^m + F_Z of the
+// parameter set, as polynomials of degree < m in z. Signing and verification combine the
+// m-vectors u_ij and the columns of the M_i matrices multiplied by z^ell (MAYO Algorithms 8 and 9).
+//
+// An element has two representations, converted into each other with From/Into:
+// - ExtensionElement stores one Gf16 per coefficient. It is simple but slow, and serves as the
+//   reference for the arithmetic.
+// - BitslicedElement stores the coefficients in the bitsliced form of the rest of the crate, one
+//   m-vector of M / 8 words. Sign and verify accumulate A, y and P*(s) on it with the two operations
+//   they need, multiplication by z and addition. There is no bitsliced product of two elements.
+use std::ops::{Add, AddAssign, Mul, Sub, SubAssign};
+
+use crate::constants::{F_Z, M};
+use crate::finite_field::{mul, Gf16};
+use crate::matrix::Vector;
+use crate::{decode_bit_sliced_array, encode_bit_sliced_array};
+
+// Coefficient i is the coefficient of z^i
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExtensionElement(pub Vector<M>);
+
+impl ExtensionElement {
+    pub fn zero() -> Self {
+        ExtensionElement(Vector::zero())
+    }
+
+    pub fn one() -> Self {
+        let mut one = Self::zero();
+        one.0[0] = Gf16::ONE;
+        one
+    }
+
+    // Reduces a polynomial of degree < 2m modulo f(z), replacing z^m by the terms of F_Z from the
+    // highest degree down
+    pub fn reduce(polynomial: &[Gf16]) -> Self {
+        assert!(
+            polynomial.len() <= 2 * M,
+            "Polynomial degree too large to reduce"
+        );
+        let mut buffer = [Gf16::ZERO; 2 * M];
+        buffer[..polynomial.len()].copy_from_slice(polynomial);
+
+        for i in (M..polynomial.len()).rev() {
+            let coefficient = buffer[i];
+            for (shift, coef) in F_Z.iter() {
+                buffer[i - M + *shift] += coefficient * Gf16::from_nibble(*coef);
+            }
+        }
+
+        let mut reduced = Self::zero();
+        reduced.0 .0.copy_from_slice(&buffer[..M]);
+        reduced
+    }
+
+    // self * z^ell for ell <= m
+    pub fn mul_by_z_pow(&self, ell: usize) -> Self {
+        assert!(ell <= M, "Shift larger than the extension degree");
+        let mut shifted = [Gf16::ZERO; 2 * M];
+        shifted[ell..ell + M].copy_from_slice(&self.0 .0);
+        Self::reduce(&shifted[..ell + M])
+    }
+
+}
+
+// Words per bit plane of a bitsliced m-vector, the bit of element i being bit i % 32 of word i / 32
+const U32_PER_TERM: usize = M / 32;
+
+// Bit b of coefficient i is bit i % 32 of word b * U32_PER_TERM + i / 32
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BitslicedElement(pub [u32; M / 8]);
+
+impl BitslicedElement {
+    pub fn zero() -> Self {
+        BitslicedElement([0u32; M / 8])
+    }
+
+    // The m-vector of U32_PER_IDX words of a bitsliced array, e.g. an entry of L or of v^t * P1 * v
+    pub fn from_words(words: &[u32]) -> Self {
+        BitslicedElement(words.try_into().expect("Slice has incorrect length"))
+    }
+
+    // Multiplies by z in place: every plane is shifted up by one element and the coefficient
+    // shifted out of z^(m-1) is reduced to F_Z times that coefficient
+    pub fn mul_by_z(&mut self) {
+        let mut top = 0u8;
+        for (b, plane) in self.0.chunks_exact_mut(U32_PER_TERM).enumerate() {
+            top |= ((plane[U32_PER_TERM - 1] >> 31) as u8) << b;
+            for w in (1..U32_PER_TERM).rev() {
+                plane[w] = (plane[w] << 1) | (plane[w - 1] >> 31);
+            }
+            plane[0] <<= 1;
+        }
+
+        for (shift, coef) in F_Z.iter() {
+            let reduced = mul(top, *coef);
+            for (b, plane) in self.0.chunks_exact_mut(U32_PER_TERM).enumerate() {
+                plane[shift / 32] ^= (((reduced >> b) & 1) as u32) << (shift % 32);
+            }
+        }
+    }
+}
+
+impl From<ExtensionElement> for BitslicedElement {
+    fn from(element: ExtensionElement) -> Self {
+        let bytes = encode_bit_sliced_array!(element.0.to_nibbles(), M);
+        let mut words = [0u32; M / 8];
+        for (word, chunk) in words.iter_mut().zip(bytes.chunks(4)) {
+            *word = u32::from_le_bytes(chunk.try_into().expect("Chunk has incorrect length"));
+        }
+        BitslicedElement(words)
+    }
+}
+
+impl From<BitslicedElement> for ExtensionElement {
+    fn from(element: BitslicedElement) -> Self {
+        ExtensionElement(Vector::from_nibbles(&decode_bit_sliced_array!(element.0)))
+    }
+}
+
+// Addition of the coefficients is XOR of the planes
+#[allow(clippy::suspicious_op_assign_impl)]
+impl AddAssign for BitslicedElement {
+    fn add_assign(&mut self, rhs: Self) {
+        for (word, rhs_word) in self.0.iter_mut().zip(rhs.0) {
+            *word ^= rhs_word;
+        }
+    }
+}
+
+impl Add for BitslicedElement {
+    type Output = Self;
+
+    fn add(mut self, rhs: Self) -> Self {
+        self += rhs;
+        self
+    }
+}
+
+impl From<Vector<M>> for ExtensionElement {
+    fn from(coefficients: Vector<M>) -> Self {
+        ExtensionElement(coefficients)
+    }
+}
+
+impl AddAssign for ExtensionElement {
+    fn add_assign(&mut self, rhs: Self) {
+        self.0 += rhs.0;
+    }
+}
+
+impl SubAssign for ExtensionElement {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.0 -= rhs.0;
+    }
+}
+
+impl Add for ExtensionElement {
+    type Output = Self;
+
+    fn add(mut self, rhs: Self) -> Self {
+        self += rhs;
+        self
+    }
+}
+
+impl Sub for ExtensionElement {
+    type Output = Self;
+
+    fn sub(mut self, rhs: Self) -> Self {
+        self -= rhs;
+        self
+    }
+}
+
+impl Mul for ExtensionElement {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        let mut product = [Gf16::ZERO; 2 * M];
+        for (i, x) in self.0.iter().enumerate() {
+            for (j, y) in rhs.0.iter().enumerate() {
+                product[i + j] += *x * *y;
+            }
+        }
+        Self::reduce(&product)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::SHIFTS;
+
+    // Polynomials over GF(16) of any degree, coefficient i of z^i
+    fn trim(mut polynomial: Vec<Gf16>) -> Vec<Gf16> {
+        while polynomial.last().is_some_and(|c| c.is_zero()) {
+            polynomial.pop();
+        }
+        polynomial
+    }
+
+    fn remainder(a: &[Gf16], b: &[Gf16]) -> Vec<Gf16> {
+        let b = trim(b.to_vec());
+        let lead_inv = b.last().expect("Division by the zero polynomial").inv();
+        let mut a = trim(a.to_vec());
+        while a.len() >= b.len() {
+            let factor = *a.last().unwrap() * lead_inv;
+            let offset = a.len() - b.len();
+            for (i, c) in b.iter().enumerate() {
+                a[offset + i] -= factor * *c;
+            }
+            a = trim(a);
+        }
+        a
+    }
+
+    fn mul_mod(a: &[Gf16], b: &[Gf16], f: &[Gf16]) -> Vec<Gf16> {
+        let mut product = vec![Gf16::ZERO; a.len() + b.len()];
+        for (i, x) in a.iter().enumerate() {
+            for (j, y) in b.iter().enumerate() {
+                product[i + j] += *x * *y;
+            }
+        }
+        remainder(&product, f)
+    }
+
+    fn gcd(a: &[Gf16], b: &[Gf16]) -> Vec<Gf16> {
+        let (mut a, mut b) = (trim(a.to_vec()), trim(b.to_vec()));
+        while !b.is_empty() {
+            let r = remainder(&a, &b);
+            a = b;
+            b = r;
+        }
+        a
+    }
+
+    // z^(16^e) mod f by 4e squarings
+    fn z_pow_q_pow(e: usize, f: &[Gf16]) -> Vec<Gf16> {
+        let mut x = remainder(&[Gf16::ZERO, Gf16::ONE], f);
+        for _ in 0..4 * e {
+            x = mul_mod(&x, &x, f);
+        }
+        x
+    }
+
+    // Rabin's test: f of degree n is irreducible over GF(16) iff z^(16^n) = z mod f and
+    // gcd(z^(16^(n/p)) - z, f) = 1 for every prime p dividing n
+    fn is_irreducible(f: &[Gf16]) -> bool {
+        let n = trim(f.to_vec()).len() - 1;
+        let z = [Gf16::ZERO, Gf16::ONE];
+        if trim(z_pow_q_pow(n, f)) != remainder(&z, f) {
+            return false;
+        }
+        (2..=n)
+            .filter(|p| n.is_multiple_of(*p) && (2..*p).all(|d| !p.is_multiple_of(d)))
+            .all(|p| {
+                let mut h = z_pow_q_pow(n / p, f);
+                h.resize(h.len().max(2), Gf16::ZERO);
+                h[1] -= Gf16::ONE;
+                gcd(&h, f).len() == 1
+            })
+    }
+
+    fn f_z() -> Vec<Gf16> {
+        let mut f = vec![Gf16::ZERO; M + 1];
+        f[M] = Gf16::ONE;
+        for (shift, coef) in F_Z.iter() {
+            f[*shift] = Gf16::from_nibble(*coef);
+        }
+        f
+    }
+
+    fn element(seed: u8) -> ExtensionElement {
+        let mut element = ExtensionElement::zero();
+        for (i, c) in element.0 .0.iter_mut().enumerate() {
+            *c = Gf16::from_nibble((i as u8).wrapping_mul(7).wrapping_add(seed) & 0xf);
+        }
+        element
+    }
+
+    #[test]
+    fn test_irreducibility_test() {
+        let nibbles = |c: &[u8]| c.iter().map(|c| Gf16::from_nibble(*c)).collect::<Vec<_>>();
+        assert!(is_irreducible(&nibbles(&[0x8, 0x1, 0x1]))); // z^2 + z + x^3, Tr(x^3) = 1
+        assert!(!is_irreducible(&nibbles(&[0x2, 0x3, 0x1]))); // (z + 1)(z + x)
+        assert!(!is_irreducible(&nibbles(&[0x1, 0x1, 0x1]))); // has roots in GF(4)
+    }
+
+    #[test]
+    fn test_f_is_irreducible() {
+        assert!(is_irreducible(&f_z()));
+    }
+
+    #[test]
+    fn test_mul_by_z_pow() {
+        let mut z = ExtensionElement::zero();
+        z.0[1] = Gf16::ONE;
+
+        let a = element(3);
+        let mut expected = a;
+        for ell in 0..=SHIFTS {
+            assert_eq!(a.mul_by_z_pow(ell), expected);
+            expected = expected * z;
+        }
+        // z^m = F_Z
+        let mut z_m = ExtensionElement::zero();
+        for (shift, coef) in F_Z.iter() {
+            z_m.0[*shift] = Gf16::from_nibble(*coef);
+        }
+        assert_eq!(ExtensionElement::one().mul_by_z_pow(M), z_m);
+    }
+
+    #[test]
+    fn test_ring_laws() {
+        let (a, b, c) = (element(1), element(5), element(11));
+        assert_eq!(a * b, b * a);
+        assert_eq!(a * (b + c), a * b + a * c);
+        assert_eq!((a * b) * c, a * (b * c));
+        assert_eq!(a * ExtensionElement::one(), a);
+        assert_eq!(a - a, ExtensionElement::zero());
+    }
+
+    #[test]
+    fn test_bitsliced_round_trip() {
+        let a = element(9);
+        assert_eq!(ExtensionElement::from(BitslicedElement::from(a)), a);
+        let words = BitslicedElement::from(a).0;
+        assert_eq!(ExtensionElement::from(BitslicedElement::from_words(&words)), a);
+    }
+
+    #[test]
+    fn test_bitsliced_add() {
+        let (a, b) = (element(2), element(6));
+        let sum = BitslicedElement::from(a) + BitslicedElement::from(b);
+        assert_eq!(ExtensionElement::from(sum), a + b);
+    }
+
+    #[test]
+    fn test_bitsliced_mul_by_z() {
+        for seed in [0, 4, 13] {
+            let a = element(seed);
+            let mut bitsliced = BitslicedElement::from(a);
+            for ell in 1..=M {
+                bitsliced.mul_by_z();
+                assert_eq!(ExtensionElement::from(bitsliced), a.mul_by_z_pow(ell));
+            }
+            bitsliced += BitslicedElement::from(a.mul_by_z_pow(M));
+            assert_eq!(bitsliced, BitslicedElement::zero());
+        }
+    }
+}
@@ -0,0 +1,331 @@
+// C interface following the NIST/SUPERCOP crypto_sign API. The symbols are prefixed with the
+// parameter set (e.g. MAYO_1_crypto_sign) so libraries for several parameter sets can be linked
+// together. build.rs generates the matching header `mayo_api.h`, which also maps the plain NIST
+// names (crypto_sign, CRYPTO_BYTES, ...) to the prefixed ones.
+//
+// All functions return 0 on success and -1 on failure.
+use std::ptr;
+use std::slice;
+
+use crate::constants::{CPK_BYTES, CSK_BYTES, SIG_BYTES};
+use crate::mayo_functionality::{compact_key_gen, expand_pk, sign, verify, CompactPublicKey};
+
+#[cfg(feature = "mayo1")]
+macro_rules! c_name {
+    ($name:literal) => {
+        concat!("MAYO_1_", $name)
+    };
+}
+#[cfg(feature = "mayo2")]
+macro_rules! c_name {
+    ($name:literal) => {
+        concat!("MAYO_2_", $name)
+    };
+}
+#[cfg(feature = "mayo3")]
+macro_rules! c_name {
+    ($name:literal) => {
+        concat!("MAYO_3_", $name)
+    };
+}
+#[cfg(feature = "mayo5")]
+macro_rules! c_name {
+    ($name:literal) => {
+        concat!("MAYO_5_", $name)
+    };
+}
+
+const SUCCESS: i32 = 0;
+const FAILURE: i32 = -1;
+
+/// Generates a key pair using the NIST randomness source (see `randombytes_init`).
+///
+/// # Safety
+/// `pk` must be valid for writes of CRYPTO_PUBLICKEYBYTES bytes and `sk` of CRYPTO_SECRETKEYBYTES bytes.
+#[export_name = c_name!("crypto_sign_keypair")]
+pub unsafe extern "C" fn crypto_sign_keypair(pk: *mut u8, sk: *mut u8) -> i32 {
+    if pk.is_null() || sk.is_null() {
+        return FAILURE;
+    }
+
+    let (cpk, csk) = compact_key_gen();
+    ptr::copy_nonoverlapping(cpk.to_bytes().as_ptr(), pk, CPK_BYTES);
+    ptr::copy_nonoverlapping(csk.as_ptr(), sk, CSK_BYTES);
+    SUCCESS
+}
+
+/// Writes the signed message (signature || message) to `sm` and its length to `smlen`.
+///
+/// # Safety
+/// `m` must be valid for reads of `mlen` bytes, `sk` of CRYPTO_SECRETKEYBYTES bytes, and `sm` must be
+/// valid for writes of `mlen + CRYPTO_BYTES` bytes. `sm` may overlap `m`.
+#[export_name = c_name!("crypto_sign")]
+pub unsafe extern "C" fn crypto_sign(
+    sm: *mut u8,
+    smlen: *mut u64,
+    m: *const u8,
+    mlen: u64,
+    sk: *const u8,
+) -> i32 {
+    if sm.is_null() || smlen.is_null() || sk.is_null() || (m.is_null() && mlen != 0) {
+        return FAILURE;
+    }
+
+    let mlen = mlen as usize;
+    let signature = sign(read_secret_key(sk), bytes(m, mlen));
+
+    // The message is moved first since it may overlap the signature. An empty message may be NULL,
+    // which ptr::copy does not accept even for a zero count
+    if mlen != 0 {
+        ptr::copy(m, sm.add(SIG_BYTES), mlen);
+    }
+    ptr::copy_nonoverlapping(signature.as_ptr(), sm, SIG_BYTES);
+    *smlen = (mlen + SIG_BYTES) as u64;
+    SUCCESS
+}
+
+/// Verifies a signed message and on success writes the message to `m` and its length to `mlen`.
+///
+/// # Safety
+/// `sm` must be valid for reads of `smlen` bytes, `pk` of CRYPTO_PUBLICKEYBYTES bytes, and `m` must be
+/// valid for writes of `smlen` bytes. `m` may overlap `sm`.
+#[export_name = c_name!("crypto_sign_open")]
+pub unsafe extern "C" fn crypto_sign_open(
+    m: *mut u8,
+    mlen: *mut u64,
+    sm: *const u8,
+    smlen: u64,
+    pk: *const u8,
+) -> i32 {
+    if m.is_null() || mlen.is_null() || sm.is_null() || pk.is_null() {
+        return FAILURE;
+    }
+    let smlen = smlen as usize;
+    if smlen < SIG_BYTES {
+        return FAILURE;
+    }
+
+    let signed_message = bytes(sm, smlen);
+    let (signature, message) = signed_message.split_at(SIG_BYTES);
+    if !verify_with_public_key(pk, signature, message) {
+        return FAILURE;
+    }
+
+    let message_len = smlen - SIG_BYTES;
+    ptr::copy(sm.add(SIG_BYTES), m, message_len);
+    *mlen = message_len as u64;
+    SUCCESS
+}
+
+/// Writes a detached signature of `m` to `sig` and its length to `siglen`.
+///
+/// # Safety
+/// `m` must be valid for reads of `mlen` bytes, `sk` of CRYPTO_SECRETKEYBYTES bytes, and `sig` must be
+/// valid for writes of CRYPTO_BYTES bytes.
+#[export_name = c_name!("crypto_sign_signature")]
+pub unsafe extern "C" fn crypto_sign_signature(
+    sig: *mut u8,
+    siglen: *mut usize,
+    m: *const u8,
+    mlen: usize,
+    sk: *const u8,
+) -> i32 {
+    if sig.is_null() || siglen.is_null() || sk.is_null() || (m.is_null() && mlen != 0) {
+        return FAILURE;
+    }
+
+    let signature = sign(read_secret_key(sk), bytes(m, mlen));
+    ptr::copy_nonoverlapping(signature.as_ptr(), sig, SIG_BYTES);
+    *siglen = SIG_BYTES;
+    SUCCESS
+}
+
+/// Verifies a detached signature of `m`.
+///
+/// # Safety
+/// `sig` must be valid for reads of `siglen` bytes, `m` of `mlen` bytes and `pk` of
+/// CRYPTO_PUBLICKEYBYTES bytes.
+#[export_name = c_name!("crypto_sign_verify")]
+pub unsafe extern "C" fn crypto_sign_verify(
+    sig: *const u8,
+    siglen: usize,
+    m: *const u8,
+    mlen: usize,
+    pk: *const u8,
+) -> i32 {
+    if sig.is_null() || pk.is_null() || (m.is_null() && mlen != 0) || siglen != SIG_BYTES {
+        return FAILURE;
+    }
+
+    if verify_with_public_key(pk, bytes(sig, siglen), bytes(m, mlen)) {
+        SUCCESS
+    } else {
+        FAILURE
+    }
+}
+
+// Slice from a C buffer, which may be NULL if `len` is 0
+unsafe fn bytes<'a>(data: *const u8, len: usize) -> &'a [u8] {
+    if len == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(data, len)
+    }
+}
+
+unsafe fn read_secret_key(sk: *const u8) -> [u8; CSK_BYTES] {
+    bytes(sk, CSK_BYTES)
+        .try_into()
+        .expect("Slice has incorrect length")
+}
+
+unsafe fn verify_with_public_key(pk: *const u8, signature: &[u8], message: &[u8]) -> bool {
+    let cpk = CompactPublicKey::from_bytes(bytes(pk, CPK_BYTES)).expect("Slice has correct length");
+    verify(expand_pk(cpk), signature, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_and_open() {
+        let mut pk = [0u8; CPK_BYTES];
+        let mut sk = [0u8; CSK_BYTES];
+        let message = b"message through the C interface";
+
+        unsafe {
+            assert_eq!(crypto_sign_keypair(pk.as_mut_ptr(), sk.as_mut_ptr()), 0);
+
+            let mut sm = vec![0u8; message.len() + SIG_BYTES];
+            let mut smlen = 0u64;
+            let result = crypto_sign(
+                sm.as_mut_ptr(),
+                &mut smlen,
+                message.as_ptr(),
+                message.len() as u64,
+                sk.as_ptr(),
+            );
+            assert_eq!(result, 0);
+            assert_eq!(smlen as usize, sm.len());
+
+            let mut opened = vec![0u8; sm.len()];
+            let mut mlen = 0u64;
+            let result = crypto_sign_open(
+                opened.as_mut_ptr(),
+                &mut mlen,
+                sm.as_ptr(),
+                smlen,
+                pk.as_ptr(),
+            );
+            assert_eq!(result, 0);
+            assert_eq!(&opened[..mlen as usize], message);
+
+            sm[SIG_BYTES] ^= 1;
+            let result = crypto_sign_open(
+                opened.as_mut_ptr(),
+                &mut mlen,
+                sm.as_ptr(),
+                smlen,
+                pk.as_ptr(),
+            );
+            assert_eq!(result, -1);
+
+            // Signing in place: the message already sits behind the space for the signature
+            let mut in_place = vec![0u8; SIG_BYTES];
+            in_place.extend_from_slice(message);
+            let message_ptr = in_place.as_ptr().add(SIG_BYTES);
+            let result = crypto_sign(
+                in_place.as_mut_ptr(),
+                &mut smlen,
+                message_ptr,
+                message.len() as u64,
+                sk.as_ptr(),
+            );
+            assert_eq!(result, 0);
+            assert_eq!(&in_place[SIG_BYTES..], message);
+            let result = crypto_sign_open(
+                opened.as_mut_ptr(),
+                &mut mlen,
+                in_place.as_ptr(),
+                smlen,
+                pk.as_ptr(),
+            );
+            assert_eq!(result, 0);
+        }
+    }
+
+    #[test]
+    fn test_sign_and_open_empty_message() {
+        let mut pk = [0u8; CPK_BYTES];
+        let mut sk = [0u8; CSK_BYTES];
+        let mut sm = [0u8; SIG_BYTES];
+        let mut smlen = 0u64;
+
+        unsafe {
+            assert_eq!(crypto_sign_keypair(pk.as_mut_ptr(), sk.as_mut_ptr()), 0);
+
+            // Empty message given as NULL
+            let result = crypto_sign(sm.as_mut_ptr(), &mut smlen, ptr::null(), 0, sk.as_ptr());
+            assert_eq!(result, 0);
+            assert_eq!(smlen as usize, SIG_BYTES);
+
+            let mut opened = [0u8; SIG_BYTES];
+            let mut mlen = 1u64;
+            let result = crypto_sign_open(
+                opened.as_mut_ptr(),
+                &mut mlen,
+                sm.as_ptr(),
+                smlen,
+                pk.as_ptr(),
+            );
+            assert_eq!(result, 0);
+            assert_eq!(mlen, 0);
+
+            assert_eq!(
+                crypto_sign(sm.as_mut_ptr(), &mut smlen, ptr::null(), 1, sk.as_ptr()),
+                -1
+            );
+        }
+    }
+
+    #[test]
+    fn test_detached_signature() {
+        let mut pk = [0u8; CPK_BYTES];
+        let mut sk = [0u8; CSK_BYTES];
+        let mut sig = [0u8; SIG_BYTES];
+        let mut siglen = 0usize;
+
+        unsafe {
+            assert_eq!(crypto_sign_keypair(pk.as_mut_ptr(), sk.as_mut_ptr()), 0);
+
+            // Empty message given as NULL
+            let result = crypto_sign_signature(
+                sig.as_mut_ptr(),
+                &mut siglen,
+                ptr::null(),
+                0,
+                sk.as_ptr(),
+            );
+            assert_eq!(result, 0);
+            assert_eq!(siglen, SIG_BYTES);
+
+            assert_eq!(
+                crypto_sign_verify(sig.as_ptr(), siglen, ptr::null(), 0, pk.as_ptr()),
+                0
+            );
+            assert_eq!(
+                crypto_sign_verify(sig.as_ptr(), siglen, b"x".as_ptr(), 1, pk.as_ptr()),
+                -1
+            );
+            assert_eq!(
+                crypto_sign_verify(sig.as_ptr(), siglen - 1, ptr::null(), 0, pk.as_ptr()),
+                -1
+            );
+            assert_eq!(
+                crypto_sign_signature(ptr::null_mut(), &mut siglen, ptr::null(), 0, sk.as_ptr()),
+                -1
+            );
+        }
+    }
+}
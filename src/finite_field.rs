@@ -1,5 +1,7 @@
 // Methods that define arithmetic over GF(16), with irreducible polynomial of degree 4 over GF(2).
 // Concretely, f(x) = x^4 + x + 1 is used.
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
 use std::u8;
 
 // Negation in GF(16) of any element is the element itself because a is it's own additive inverse (where 0 is the additive identity).
@@ -64,6 +66,158 @@ pub fn div(x: u8, y: u8) -> u8 {
     mul(x, inv(y))
 }
 
+// Multiplies each of the 16 elements packed as nibbles in x by y (gf16v_mul_u64 of MAYO-C).
+// x * y is the sum of x * 2^i over the set bits i of y, and x * 2 shifts every nibble up by one
+// bit, replacing the overflow x^4 by x + 1 (e.g. 3).
+pub fn mul_packed(x: u64, y: u8) -> u64 {
+    const MSB: u64 = 0x8888_8888_8888_8888;
+    let mut x = x;
+    let mut res = x * (y & 1) as u64;
+    for i in 1..4 {
+        let msb = x & MSB;
+        x = ((x ^ msb) << 1) ^ ((msb >> 3) * 3);
+        res ^= x * ((y >> i) & 1) as u64;
+    }
+    res
+}
+
+// An element of GF(16), always in 0..16. The operators are the functions above, except that
+// division by zero panics instead of returning zero.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct Gf16(u8);
+
+impl Gf16 {
+    pub const ZERO: Gf16 = Gf16(0);
+    pub const ONE: Gf16 = Gf16(1);
+
+    pub fn new(x: u8) -> Result<Self, &'static str> {
+        if x < 16 {
+            Ok(Gf16(x))
+        } else {
+            Err("Value is not an element of GF(16)")
+        }
+    }
+
+    // The element `x` for values below 16 by construction, e.g. decoded from packed nibbles.
+    // Unlike `new` the range is only checked in debug builds.
+    #[inline]
+    pub const fn from_nibble(x: u8) -> Self {
+        debug_assert!(x < 16);
+        Gf16(x)
+    }
+
+    #[inline]
+    pub const fn value(self) -> u8 {
+        self.0
+    }
+
+    #[inline]
+    pub const fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+
+    // The multiplicative inverse, zero for zero (as `inv`)
+    #[inline]
+    pub fn inv(self) -> Self {
+        Gf16(inv(self.0))
+    }
+}
+
+impl TryFrom<u8> for Gf16 {
+    type Error = &'static str;
+
+    fn try_from(x: u8) -> Result<Self, Self::Error> {
+        Gf16::new(x)
+    }
+}
+
+impl From<Gf16> for u8 {
+    fn from(x: Gf16) -> u8 {
+        x.0
+    }
+}
+
+impl Add for Gf16 {
+    type Output = Gf16;
+
+    #[inline]
+    fn add(self, rhs: Gf16) -> Gf16 {
+        Gf16(add(self.0, rhs.0))
+    }
+}
+
+impl Sub for Gf16 {
+    type Output = Gf16;
+
+    #[inline]
+    fn sub(self, rhs: Gf16) -> Gf16 {
+        Gf16(sub(self.0, rhs.0))
+    }
+}
+
+impl Mul for Gf16 {
+    type Output = Gf16;
+
+    #[inline]
+    fn mul(self, rhs: Gf16) -> Gf16 {
+        Gf16(mul(self.0, rhs.0))
+    }
+}
+
+impl Div for Gf16 {
+    type Output = Gf16;
+
+    #[inline]
+    fn div(self, rhs: Gf16) -> Gf16 {
+        assert!(!rhs.is_zero(), "Division by zero in GF(16)");
+        Gf16(div(self.0, rhs.0))
+    }
+}
+
+impl Neg for Gf16 {
+    type Output = Gf16;
+
+    #[inline]
+    fn neg(self) -> Gf16 {
+        Gf16(neg(self.0))
+    }
+}
+
+impl AddAssign for Gf16 {
+    #[inline]
+    fn add_assign(&mut self, rhs: Gf16) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Gf16 {
+    #[inline]
+    fn sub_assign(&mut self, rhs: Gf16) {
+        *self = *self - rhs;
+    }
+}
+
+impl MulAssign for Gf16 {
+    #[inline]
+    fn mul_assign(&mut self, rhs: Gf16) {
+        *self = *self * rhs;
+    }
+}
+
+impl DivAssign for Gf16 {
+    #[inline]
+    fn div_assign(&mut self, rhs: Gf16) {
+        *self = *self / rhs;
+    }
+}
+
+impl Sum for Gf16 {
+    fn sum<I: Iterator<Item = Gf16>>(iter: I) -> Gf16 {
+        iter.fold(Gf16::ZERO, Add::add)
+    }
+}
+
 #[macro_export]
 macro_rules! matrix_add {
     ($a:expr, $b:expr, $rows:expr, $cols:expr) => {{
@@ -227,4 +381,94 @@ mod tests {
         assert_eq!(inv(0x3), 0xe); // (x + 1)'s inverse is x^3 + x^2 + x
         assert_eq!(inv(0x4), 0xd); // x^2's inverse is x^3 + x^2 + 1
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_gf16_operators() {
+        for x in 0..16 {
+            let a = Gf16::new(x).unwrap();
+            assert_eq!(u8::from(-a), neg(x));
+            assert_eq!(a.inv().value(), inv(x));
+            for y in 0..16 {
+                let b = Gf16::try_from(y).unwrap();
+                assert_eq!((a + b).value(), add(x, y));
+                assert_eq!((a - b).value(), sub(x, y));
+                assert_eq!((a * b).value(), mul(x, y));
+                if y != 0 {
+                    assert_eq!((a / b).value(), div(x, y));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_gf16_range() {
+        assert_eq!(Gf16::new(15), Ok(Gf16::from_nibble(0xf)));
+        assert!(Gf16::new(16).is_err());
+        assert!(Gf16::try_from(0xf0).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "Division by zero")]
+    fn test_gf16_division_by_zero() {
+        let _ = Gf16::ONE / Gf16::ZERO;
+    }
+
+    // Field axioms over random elements
+    mod properties {
+        use super::*;
+        use proptest::prelude::*;
+
+        fn element() -> impl Strategy<Value = u8> {
+            0u8..16
+        }
+
+        proptest! {
+            #[test]
+            fn test_add_is_abelian_group(x in element(), y in element(), z in element()) {
+                prop_assert_eq!(add(add(x, y), z), add(x, add(y, z)));
+                prop_assert_eq!(add(x, y), add(y, x));
+                prop_assert_eq!(add(x, 0), x);
+                prop_assert_eq!(add(x, neg(x)), 0);
+                prop_assert_eq!(sub(add(x, y), y), x);
+                prop_assert!(add(x, y) < 16);
+            }
+
+            #[test]
+            fn test_mul_is_commutative_monoid(x in element(), y in element(), z in element()) {
+                prop_assert_eq!(mul(mul(x, y), z), mul(x, mul(y, z)));
+                prop_assert_eq!(mul(x, y), mul(y, x));
+                prop_assert_eq!(mul(x, 1), x);
+                prop_assert_eq!(mul(x, 0), 0);
+                prop_assert!(mul(x, y) < 16);
+            }
+
+            #[test]
+            fn test_distributivity(x in element(), y in element(), z in element()) {
+                prop_assert_eq!(mul(x, add(y, z)), add(mul(x, y), mul(x, z)));
+                prop_assert_eq!(mul(add(x, y), z), add(mul(x, z), mul(y, z)));
+            }
+
+            #[test]
+            fn test_inverse(x in 1u8..16, y in element()) {
+                prop_assert_eq!(mul(x, inv(x)), 1);
+                prop_assert_eq!(inv(inv(x)), x);
+                prop_assert_eq!(div(mul(y, x), x), y);
+            }
+
+            // No zero divisors, as f(x) = x^4 + x + 1 is irreducible
+            #[test]
+            fn test_no_zero_divisors(x in 1u8..16, y in 1u8..16) {
+                prop_assert_ne!(mul(x, y), 0);
+            }
+
+            #[test]
+            fn test_mul_packed(x in any::<u64>(), y in element()) {
+                let product = mul_packed(x, y);
+                for i in 0..16 {
+                    let nibble = ((x >> (4 * i)) & 0xf) as u8;
+                    prop_assert_eq!(((product >> (4 * i)) & 0xf) as u8, mul(nibble, y));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,91 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Known answer test generation through the C interface (see src/ffi.rs), following NIST's
+// PQCgenKAT_sign.c. Writes the same .rsp format as write_and_compare_kat_file.rs.
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "mayo_api.h"
+#include "rng.h"
+
+#define KAT_COUNT 100
+
+static void fprint_bstr(FILE *fp, const char *s, const unsigned char *a, unsigned long long l) {
+    fprintf(fp, "%s", s);
+    for (unsigned long long i = 0; i < l; i++) {
+        fprintf(fp, "%02X", a[i]);
+    }
+    if (l == 0) {
+        fprintf(fp, "00");
+    }
+    fprintf(fp, "\n");
+}
+
+// Writes the KAT file to `path`. Returns 0 on success, -1 if a signature does not verify
+// or a file could not be written.
+int mayo_ffi_genkat(const char *path) {
+    unsigned char seed[KAT_COUNT][48];
+    unsigned char *msg[KAT_COUNT];
+    unsigned char entropy_input[48];
+    unsigned char pk[CRYPTO_PUBLICKEYBYTES], sk[CRYPTO_SECRETKEYBYTES];
+    int ret = 0;
+
+    FILE *fp = fopen(path, "w");
+    if (fp == NULL) {
+        return -1;
+    }
+
+    for (int i = 0; i < 48; i++) {
+        entropy_input[i] = (unsigned char)i;
+    }
+    randombytes_init(entropy_input, NULL, 256);
+
+    // Create all seeds and messages
+    for (int i = 0; i < KAT_COUNT; i++) {
+        unsigned long long mlen = 33 * (i + 1);
+        randombytes(seed[i], 48);
+        msg[i] = malloc(mlen);
+        randombytes(msg[i], mlen);
+    }
+
+    fprintf(fp, "# %s\n\n", CRYPTO_ALGNAME);
+
+    for (int i = 0; i < KAT_COUNT && ret == 0; i++) {
+        unsigned long long mlen = 33 * (i + 1);
+        unsigned long long smlen, mlen1;
+        unsigned char *sm = malloc(mlen + CRYPTO_BYTES);
+        unsigned char *m1 = malloc(mlen + CRYPTO_BYTES);
+
+        randombytes_init(seed[i], NULL, 256);
+
+        if (crypto_sign_keypair(pk, sk) != 0 ||
+            crypto_sign(sm, &smlen, msg[i], mlen, sk) != 0 ||
+            crypto_sign_open(m1, &mlen1, sm, smlen, pk) != 0 ||
+            mlen1 != mlen || memcmp(m1, msg[i], mlen) != 0) {
+            ret = -1;
+        }
+
+        fprintf(fp, "count = %d\n", i);
+        fprint_bstr(fp, "seed = ", seed[i], 48);
+        fprintf(fp, "mlen = %llu\n", mlen);
+        fprint_bstr(fp, "msg = ", msg[i], mlen);
+        fprint_bstr(fp, "pk = ", pk, CRYPTO_PUBLICKEYBYTES);
+        fprint_bstr(fp, "sk = ", sk, CRYPTO_SECRETKEYBYTES);
+        fprintf(fp, "smlen = %llu\n", smlen);
+        fprint_bstr(fp, "sm = ", sm, smlen);
+        fprintf(fp, "\n");
+
+        free(sm);
+        free(m1);
+    }
+
+    for (int i = 0; i < KAT_COUNT; i++) {
+        free(msg[i]);
+    }
+    if (fclose(fp) != 0) {
+        ret = -1;
+    }
+    return ret;
+}
@@ -167,9 +167,11 @@ void arm_aes128_free_schedule(void *schedule) {
 
 
 static void arm_aes128_ctr_enc_sch(const void *schedule, uint8_t *out,
-                                        size_t out_len) {
+                                        size_t out_len, uint64_t block_offset) {
     uint8x16_t mask = {0, 1, 2, 3, 4, 5, 6, 7, 15, 14, 13, 12, 11, 10, 9, 8}; 
     uint8x16_t block = vdupq_n_u8(0); // Initialize block to zero
+    // Move the big-endian counter forward to the first requested block
+    block = vqtbl1q_u8(vreinterpretq_u8_u64(vaddq_u64(vreinterpretq_u64_u8(vqtbl1q_u8(block, mask)), (uint64x2_t) {0,block_offset}) ), mask);
     while (out_len >= 16) {
         arm_aes128_encrypt(schedule, block, out);
         out += 16;
@@ -187,7 +189,17 @@ int AES_128_CTR(unsigned char *output, size_t outputByteLen,
                    const unsigned char *input) {
     void *schedule = NULL;
     arm_aes128_load_schedule(input, &schedule);
-    arm_aes128_ctr_enc_sch(schedule, output, outputByteLen);
+    arm_aes128_ctr_enc_sch(schedule, output, outputByteLen, 0);
+    arm_aes128_free_schedule(schedule);
+    return (int)outputByteLen;
+}
+
+// Same keystream as AES_128_CTR, but starting at block number blockOffset
+int AES_128_CTR_OFFSET(unsigned char *output, size_t outputByteLen,
+                       const unsigned char *input, uint32_t blockOffset) {
+    void *schedule = NULL;
+    arm_aes128_load_schedule(input, &schedule);
+    arm_aes128_ctr_enc_sch(schedule, output, outputByteLen, blockOffset);
     arm_aes128_free_schedule(schedule);
     return (int)outputByteLen;
 }
@@ -610,11 +610,10 @@ static void aes_ecb(unsigned char *out, const unsigned char *in, size_t nblocks,
     }
 }
 
-static void aes_ctr(unsigned char *out, size_t outlen, const unsigned char *iv, const uint64_t *rkeys, unsigned int nrounds)
+static void aes_ctr(unsigned char *out, size_t outlen, const unsigned char *iv, const uint64_t *rkeys, unsigned int nrounds, uint32_t cc)
 {
     uint32_t ivw[16];
     size_t i;
-    uint32_t cc = 0;
 
     br_range_dec32le(ivw, 3, iv);
     memcpy(ivw + 4, ivw, 3 * sizeof(uint32_t));
@@ -704,7 +703,7 @@ void aes128_ecb(unsigned char *out, const unsigned char *in, size_t nblocks, con
 
 void aes128_ctr(unsigned char *out, size_t outlen, const unsigned char *iv, const aes128ctx *ctx)
 {
-    aes_ctr(out, outlen, iv, ctx->sk_exp, 10);
+    aes_ctr(out, outlen, iv, ctx->sk_exp, 10, 0);
 }
 
 void aes192_ecb(unsigned char *out, const unsigned char *in, size_t nblocks, const aes192ctx *ctx)
@@ -714,7 +713,7 @@ void aes192_ecb(unsigned char *out, const unsigned char *in, size_t nblocks, con
 
 void aes192_ctr(unsigned char *out, size_t outlen, const unsigned char *iv, const aes192ctx *ctx)
 {
-    aes_ctr(out, outlen, iv, ctx->sk_exp, 12);
+    aes_ctr(out, outlen, iv, ctx->sk_exp, 12, 0);
 }
 
 void aes256_ecb(unsigned char *out, const unsigned char *in, size_t nblocks, const aes256ctx *ctx)
@@ -724,7 +723,7 @@ void aes256_ecb(unsigned char *out, const unsigned char *in, size_t nblocks, con
 
 void aes256_ctr(unsigned char *out, size_t outlen, const unsigned char *iv, const aes256ctx *ctx)
 {
-    aes_ctr(out, outlen, iv, ctx->sk_exp, 14);
+    aes_ctr(out, outlen, iv, ctx->sk_exp, 14, 0);
 }
 
 void aes128_ctx_release(aes128ctx *r)
@@ -755,6 +754,20 @@ int AES_128_CTR(unsigned char *output, size_t outputByteLen,
     return (int)outputByteLen;
 }
 
+// Same keystream as AES_128_CTR, but starting at block number blockOffset
+int AES_128_CTR_OFFSET(unsigned char *output, size_t outputByteLen,
+                       const unsigned char *input, uint32_t blockOffset)
+{
+    aes128ctx ctx;
+    unsigned char iv[16] = {0};
+
+    aes128_ctr_keyexp(&ctx, input);
+    aes_ctr(output, outputByteLen, iv, ctx.sk_exp, 10, blockOffset);
+    aes128_ctx_release(&ctx);
+
+    return (int)outputByteLen;
+}
+
 
 void AES_256_ECB(const uint8_t *input, const unsigned char *key, unsigned char *output)
 {
@@ -20,4 +20,7 @@ int AES_128_CTR(unsigned char *output, size_t outputByteLen,
                 const unsigned char *input, size_t inputByteLen);
 #endif
 
+int AES_128_CTR_OFFSET(unsigned char *output, size_t outputByteLen,
+                       const unsigned char *input, uint32_t blockOffset);
+
 #endif
\ No newline at end of file
@@ -1,5 +1,11 @@
 use std::os::raw as ccty;
 
+// Context for the incremental SHAKE256 API in fips202.c
+#[repr(C)]
+pub struct shake256incctx {
+    pub ctx: *mut u64,
+}
+
 extern "C" {
     pub fn randombytes_init_nist(
         entropy_input: *const ccty::c_uchar, // Pointer to const u8 for C compatibility
@@ -23,6 +29,13 @@ extern "C" {
         input: *const ccty::c_uchar,
     );
 
+    pub fn AES_128_CTR_OFFSET(
+        output: *mut ccty::c_uint,
+        outputByteLen: ccty::c_ulonglong,
+        input: *const ccty::c_uchar,
+        blockOffset: ccty::c_uint,
+    );
+
 // }
     pub fn shake256(
         output: *mut ccty::c_uchar,
@@ -30,4 +43,26 @@ extern "C" {
         input: *const ccty::c_uchar,
         inputByteLen: ccty::c_ulonglong,
     );
+
+    pub fn shake256_inc_init(state: *mut shake256incctx);
+
+    pub fn shake256_inc_absorb(
+        state: *mut shake256incctx,
+        input: *const ccty::c_uchar,
+        inlen: usize,
+    );
+
+    pub fn shake256_inc_finalize(state: *mut shake256incctx);
+
+    pub fn shake256_inc_squeeze(output: *mut ccty::c_uchar, outlen: usize, state: *mut shake256incctx);
+
+    pub fn shake256_inc_ctx_release(state: *mut shake256incctx);
+
+    pub fn cshake256_inc_init(
+        state: *mut shake256incctx,
+        customization: *const ccty::c_uchar,
+        customization_len: usize,
+    );
+
+    pub fn cshake256_inc_finalize(state: *mut shake256incctx);
 }
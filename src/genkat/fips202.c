@@ -133,6 +133,12 @@ void shake256_inc_ctx_clone(shake256incctx *dest, const shake256incctx *src);
 /* Free the state */
 void shake256_inc_ctx_release(shake256incctx *state);
 
+/* Initialize incremental cSHAKE256 with an empty function name and the given
+ * customization string. Absorb, squeeze and release as for SHAKE256. */
+void cshake256_inc_init(shake256incctx *state, const uint8_t *customization, size_t customization_len);
+/* Prepares for squeeze phase of cSHAKE256 */
+void cshake256_inc_finalize(shake256incctx *state);
+
 /* One-stop SHAKE128 call */
 void shake128(uint8_t *output, size_t outlen,
               const uint8_t *input, size_t inlen);
@@ -798,6 +804,53 @@ void shake256_inc_ctx_release(shake256incctx *state)
     free(state->ctx);
 }
 
+/* left_encode of NIST SP 800-185, returns the number of bytes written (at most 9) */
+static size_t left_encode(uint8_t *output, uint64_t x)
+{
+    size_t n = 1;
+    size_t i;
+
+    while (n < 8 && (x >> (8 * n)) != 0)
+    {
+        n++;
+    }
+    output[0] = (uint8_t)n;
+    for (i = 1; i <= n; i++)
+    {
+        output[i] = (uint8_t)(x >> (8 * (n - i)));
+    }
+    return n + 1;
+}
+
+void cshake256_inc_init(shake256incctx *state, const uint8_t *customization, size_t customization_len)
+{
+    uint8_t encoded[9];
+    uint8_t zeros[SHAKE256_RATE] = {0};
+    size_t n;
+    size_t absorbed = 0;
+
+    shake256_inc_init(state);
+
+    /* bytepad(encode_string(N) || encode_string(S), rate) with N empty */
+    n = left_encode(encoded, SHAKE256_RATE);
+    keccak_inc_absorb(state->ctx, SHAKE256_RATE, encoded, n);
+    absorbed += n;
+    n = left_encode(encoded, 0);
+    keccak_inc_absorb(state->ctx, SHAKE256_RATE, encoded, n);
+    absorbed += n;
+    n = left_encode(encoded, 8 * (uint64_t)customization_len);
+    keccak_inc_absorb(state->ctx, SHAKE256_RATE, encoded, n);
+    absorbed += n;
+    keccak_inc_absorb(state->ctx, SHAKE256_RATE, customization, customization_len);
+    absorbed += customization_len;
+    keccak_inc_absorb(state->ctx, SHAKE256_RATE, zeros, (SHAKE256_RATE - absorbed % SHAKE256_RATE) % SHAKE256_RATE);
+}
+
+void cshake256_inc_finalize(shake256incctx *state)
+{
+    keccak_inc_finalize(state->ctx, SHAKE256_RATE, 0x04);
+}
+
 /*************************************************
  * Name:        shake128_absorb
  *
@@ -0,0 +1,454 @@
+// Known answer tests in the NIST format. Parses request (.req) and response files into typed
+// records and checks them field by field against the compiled parameter set.
+//
+// A record is a block of `name = value` lines separated by empty lines:
+//   count, seed, mlen, msg, pk, sk, smlen, sm
+// Request files leave pk, sk, smlen and sm empty. A `# MAYO_n` line names the parameter set.
+// Parsing does not depend on the compiled parameter set, so the KAT files of every parameter set
+// can be loaded by the same binary.
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::constants::VERSION;
+use crate::crypto_primitives::{safe_random_bytes, safe_random_bytes_init};
+use crate::mayo_functionality::{api_sign, api_sign_open, compact_key_gen};
+use crate::utils::{bytes_to_hex_string, hex_string_to_bytes};
+
+pub const KAT_SEED_BYTES: usize = 48;
+
+// Parameter sets with KAT files in KAT_DIRECTORY
+pub const KAT_VERSIONS: [&str; 4] = ["MAYO_1", "MAYO_2", "MAYO_3", "MAYO_5"];
+
+pub const KAT_DIRECTORY: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/genkat/Results MAYO");
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KatRecord {
+    pub count: usize,
+    pub seed: [u8; KAT_SEED_BYTES],
+    pub mlen: usize,
+    pub msg: Vec<u8>,
+    // The expected results, None in request files
+    pub pk: Option<Vec<u8>>,
+    pub sk: Option<Vec<u8>>,
+    pub smlen: Option<usize>,
+    pub sm: Option<Vec<u8>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KatFile {
+    // Parameter set from the `# MAYO_n` header, if present
+    pub algorithm: Option<String>,
+    pub records: Vec<KatRecord>,
+}
+
+#[derive(Debug)]
+pub enum KatError {
+    Io(io::Error),
+    // Line numbers start at 1
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for KatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KatError::Io(error) => write!(f, "Could not read the KAT file: {}", error),
+            KatError::Parse { line, message } => write!(f, "Line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for KatError {}
+
+impl From<io::Error> for KatError {
+    fn from(error: io::Error) -> Self {
+        KatError::Io(error)
+    }
+}
+
+// A field of a record that does not match what the implementation produces
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KatMismatch {
+    // None for mismatches of the whole file (the algorithm header)
+    pub count: Option<usize>,
+    pub field: &'static str,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl fmt::Display for KatMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.count {
+            Some(count) => write!(f, "count = {}, {}: ", count, self.field)?,
+            None => write!(f, "{}: ", self.field)?,
+        }
+        write!(f, "expected {}, got {}", self.expected, self.actual)
+    }
+}
+
+// Path of the response file of a parameter set, e.g. "PQCsignKAT_24_MAYO_1.txt"
+pub fn kat_response_path(version: &str) -> Option<PathBuf> {
+    kat_path(version, "txt")
+}
+
+// Path of the request file of a parameter set, e.g. "PQCsignKAT_24_MAYO_1.req"
+pub fn kat_request_path(version: &str) -> Option<PathBuf> {
+    kat_path(version, "req")
+}
+
+fn kat_path(version: &str, extension: &str) -> Option<PathBuf> {
+    let suffix = format!("_{}.{}", version, extension);
+    fs::read_dir(KAT_DIRECTORY)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .find(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("PQCsignKAT_") && name.ends_with(&suffix))
+        })
+}
+
+pub fn read_kat_file<P: AsRef<Path>>(path: P) -> Result<KatFile, KatError> {
+    parse_kat(&fs::read_to_string(path)?)
+}
+
+// Fields of a record in the order they are written
+const FIELDS: [&str; 8] = ["count", "seed", "mlen", "msg", "pk", "sk", "smlen", "sm"];
+
+pub fn parse_kat(contents: &str) -> Result<KatFile, KatError> {
+    let mut algorithm = None;
+    let mut records = Vec::new();
+
+    // Values of the current record by index in FIELDS, and the line it started on
+    let mut values: [Option<&str>; 8] = [None; 8];
+    let mut record_line = 0;
+
+    for (index, line) in contents.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+
+        if line.is_empty() {
+            if values.iter().any(|value| value.is_some()) {
+                records.push(parse_record(&values, record_line)?);
+                values = [None; 8];
+            }
+            continue;
+        }
+
+        if let Some(comment) = line.strip_prefix('#') {
+            if algorithm.is_none() && records.is_empty() {
+                algorithm = Some(comment.trim().to_string());
+            }
+            continue;
+        }
+
+        let (name, value) = line.split_once('=').ok_or_else(|| KatError::Parse {
+            line: line_number,
+            message: format!("Expected `name = value`, got `{}`", line),
+        })?;
+        let name = name.trim();
+        let field = FIELDS
+            .iter()
+            .position(|field| *field == name)
+            .ok_or_else(|| KatError::Parse {
+                line: line_number,
+                message: format!("Unknown field `{}`", name),
+            })?;
+        if values[field].is_some() {
+            return Err(KatError::Parse {
+                line: line_number,
+                message: format!("Duplicate field `{}`", name),
+            });
+        }
+        if values.iter().all(|value| value.is_none()) {
+            record_line = line_number;
+        }
+        values[field] = Some(value.trim());
+    }
+
+    if values.iter().any(|value| value.is_some()) {
+        records.push(parse_record(&values, record_line)?);
+    }
+
+    Ok(KatFile { algorithm, records })
+}
+
+fn parse_record(values: &[Option<&str>; 8], line: usize) -> Result<KatRecord, KatError> {
+    let error = |message: String| KatError::Parse { line, message };
+
+    let field = |index: usize| {
+        values[index].ok_or_else(|| error(format!("Record is missing `{}`", FIELDS[index])))
+    };
+    let number = |index: usize| {
+        field(index)?
+            .parse::<usize>()
+            .map_err(|_| error(format!("`{}` is not a number", FIELDS[index])))
+    };
+    let bytes = |index: usize| {
+        let hex = field(index)?;
+        if hex.len() % 2 != 0 || !hex.bytes().all(|c| c.is_ascii_hexdigit()) {
+            return Err(error(format!("`{}` is not a hex string", FIELDS[index])));
+        }
+        Ok(hex_string_to_bytes(hex))
+    };
+    // pk, sk, smlen and sm are empty in request files
+    let is_empty = |index: usize| values[index].is_none_or(|value| value.is_empty());
+
+    let count = number(0)?;
+    let seed = bytes(1)?
+        .try_into()
+        .map_err(|_| error(format!("`seed` must be {} bytes", KAT_SEED_BYTES)))?;
+
+    // An empty message is written as "00"
+    let mlen = number(2)?;
+    let mut msg = bytes(3)?;
+    if mlen == 0 && msg == [0] {
+        msg.clear();
+    }
+    if msg.len() != mlen {
+        return Err(error(format!(
+            "`msg` has {} bytes, but mlen = {}",
+            msg.len(),
+            mlen
+        )));
+    }
+
+    let pk = if is_empty(4) { None } else { Some(bytes(4)?) };
+    let sk = if is_empty(5) { None } else { Some(bytes(5)?) };
+    let smlen = if is_empty(6) { None } else { Some(number(6)?) };
+    let sm = if is_empty(7) { None } else { Some(bytes(7)?) };
+    if let (Some(smlen), Some(sm)) = (smlen, &sm) {
+        if sm.len() != smlen {
+            return Err(error(format!(
+                "`sm` has {} bytes, but smlen = {}",
+                sm.len(),
+                smlen
+            )));
+        }
+    }
+
+    Ok(KatRecord {
+        count,
+        seed,
+        mlen,
+        msg,
+        pk,
+        sk,
+        smlen,
+        sm,
+    })
+}
+
+// Reproduces a record with the compiled parameter set: reseeds the NIST randomness source with
+// the record's seed, generates a key pair and signs the message, as PQCgenKAT_sign does.
+// Returns every field that differs. Fields missing in the record (request files) are not compared.
+//
+// This reseeds the global randomness source, so it must not run concurrently with other code
+// drawing randomness.
+pub fn check_kat_record(record: &KatRecord) -> Vec<KatMismatch> {
+    let mut mismatches = Vec::new();
+
+    let mut seed = record.seed;
+    safe_random_bytes_init(&mut seed, &[0u8; KAT_SEED_BYTES], 256);
+
+    let (cpk, csk) = compact_key_gen();
+    let pk = cpk.to_bytes();
+    let sm = api_sign(record.msg.clone(), csk);
+    let (valid, opened) = api_sign_open(sm.clone(), cpk);
+
+    let mut compare = |field: &'static str, expected: Option<&[u8]>, actual: &[u8]| {
+        if let Some(expected) = expected {
+            if let Some(mismatch) = compare_bytes(record.count, field, expected, actual) {
+                mismatches.push(mismatch);
+            }
+        }
+    };
+    compare("pk", record.pk.as_deref(), &pk);
+    compare("sk", record.sk.as_deref(), &csk);
+    compare("sm", record.sm.as_deref(), &sm);
+
+    if let Some(smlen) = record.smlen {
+        if smlen != sm.len() {
+            mismatches.push(KatMismatch {
+                count: Some(record.count),
+                field: "smlen",
+                expected: smlen.to_string(),
+                actual: sm.len().to_string(),
+            });
+        }
+    }
+    if !valid || opened != record.msg {
+        mismatches.push(KatMismatch {
+            count: Some(record.count),
+            field: "open",
+            expected: "the signed message to verify".to_string(),
+            actual: "verification failure".to_string(),
+        });
+    }
+
+    mismatches
+}
+
+// Checks all records of a KAT file and that it is for the compiled parameter set
+pub fn check_kat_file(file: &KatFile) -> Vec<KatMismatch> {
+    if file.algorithm.as_deref() != Some(VERSION) {
+        return vec![KatMismatch {
+            count: None,
+            field: "algorithm",
+            expected: VERSION.to_string(),
+            actual: file.algorithm.clone().unwrap_or_default(),
+        }];
+    }
+
+    file.records.iter().flat_map(check_kat_record).collect()
+}
+
+// Checks the inputs of the records (count, seed, mlen and msg) against the NIST KAT generator,
+// which seeds the randomness source with the bytes 0..47 and then draws all seeds and messages.
+// The inputs are the same for every parameter set. Like `check_kat_record` this reseeds the
+// global randomness source.
+pub fn check_kat_inputs(file: &KatFile) -> Vec<KatMismatch> {
+    let mut mismatches = Vec::new();
+
+    let mut entropy_input: [u8; KAT_SEED_BYTES] = std::array::from_fn(|i| i as u8);
+    safe_random_bytes_init(&mut entropy_input, &[0u8; KAT_SEED_BYTES], 256);
+
+    let mut inputs = Vec::with_capacity(file.records.len());
+    for count in 0..file.records.len() {
+        let mut seed = [0u8; KAT_SEED_BYTES];
+        safe_random_bytes(&mut seed, KAT_SEED_BYTES as u64);
+        let mlen = 33 * (count + 1);
+        let mut msg = vec![0u8; mlen];
+        safe_random_bytes(&mut msg, mlen as u64);
+        inputs.push((seed, msg));
+    }
+
+    for (count, (record, (seed, msg))) in file.records.iter().zip(&inputs).enumerate() {
+        let mut mismatch = |field: &'static str, expected: String, actual: String| {
+            mismatches.push(KatMismatch {
+                count: Some(record.count),
+                field,
+                expected,
+                actual,
+            })
+        };
+        if record.count != count {
+            mismatch("count", count.to_string(), record.count.to_string());
+        }
+        if record.mlen != msg.len() {
+            mismatch("mlen", msg.len().to_string(), record.mlen.to_string());
+        }
+        // The generated values are the expected ones here
+        mismatches.extend(compare_bytes(record.count, "seed", seed, &record.seed));
+        mismatches.extend(compare_bytes(record.count, "msg", msg, &record.msg));
+    }
+
+    mismatches
+}
+
+// Reports the first differing byte, so long fields give a readable message
+fn compare_bytes(
+    count: usize,
+    field: &'static str,
+    expected: &[u8],
+    actual: &[u8],
+) -> Option<KatMismatch> {
+    if expected == actual {
+        return None;
+    }
+    let position = expected
+        .iter()
+        .zip(actual)
+        .position(|(e, a)| e != a)
+        .unwrap_or(expected.len().min(actual.len()));
+
+    // A few bytes of context from the first difference on
+    let excerpt = |bytes: &[u8]| {
+        let end = bytes.len().min(position + 8);
+        format!(
+            "{} bytes, at byte {}: {}",
+            bytes.len(),
+            position,
+            bytes_to_hex_string(&bytes[position.min(end)..end], false)
+        )
+    };
+
+    Some(KatMismatch {
+        count: Some(count),
+        field,
+        expected: excerpt(expected),
+        actual: excerpt(actual),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_all_parameter_sets() {
+        // (version, pk bytes, sk bytes, signature bytes) of every parameter set
+        let sizes = [
+            ("MAYO_1", 1168, 24, 321),
+            ("MAYO_2", 5488, 24, 180),
+            ("MAYO_3", 2656, 32, 577),
+            ("MAYO_5", 5008, 40, 838),
+        ];
+
+        for (version, pk_bytes, sk_bytes, sig_bytes) in sizes {
+            let response = read_kat_file(kat_response_path(version).unwrap()).unwrap();
+            assert_eq!(response.algorithm.as_deref(), Some(version));
+            assert_eq!(response.records.len(), 100);
+
+            let request = read_kat_file(kat_request_path(version).unwrap()).unwrap();
+            assert_eq!(request.records.len(), 100);
+
+            for (index, (rsp, req)) in response.records.iter().zip(&request.records).enumerate() {
+                assert_eq!(rsp.count, index);
+                assert_eq!(rsp.mlen, 33 * (index + 1));
+                assert_eq!(rsp.pk.as_ref().unwrap().len(), pk_bytes);
+                assert_eq!(rsp.sk.as_ref().unwrap().len(), sk_bytes);
+                assert_eq!(rsp.smlen, Some(rsp.mlen + sig_bytes));
+
+                // The request holds the same inputs without results
+                assert_eq!(
+                    (req.count, req.seed, &req.msg),
+                    (rsp.count, rsp.seed, &rsp.msg)
+                );
+                assert!(
+                    req.pk.is_none() && req.sk.is_none() && req.smlen.is_none() && req.sm.is_none()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let seed = "00".repeat(KAT_SEED_BYTES);
+        let record = format!("count = 0\nseed = {}\nmlen = 1\nmsg = AB\n", seed);
+        let file = parse_kat(&format!("# MAYO_1\n\n{}", record)).unwrap();
+        assert_eq!(file.records[0].msg, vec![0xAB]);
+
+        // Empty message written as "00"
+        let empty = format!("count = 0\nseed = {}\nmlen = 0\nmsg = 00\n", seed);
+        assert!(parse_kat(&empty).unwrap().records[0].msg.is_empty());
+
+        let error_line = |contents: &str| match parse_kat(contents) {
+            Err(KatError::Parse { line, .. }) => line,
+            other => panic!("Expected a parse error, got {:?}", other),
+        };
+        assert_eq!(error_line(&record.replace("mlen = 1", "mlen = 2")), 1);
+        assert_eq!(error_line(&record.replace("msg = AB", "msg = ABC")), 1);
+        assert_eq!(error_line(&record.replace("msg = AB", "msg = XY")), 1);
+        assert_eq!(error_line(&record.replace("mlen = 1\n", "")), 1);
+        assert_eq!(
+            error_line(&format!("\n\n{}smlen = 5\nsm = 00\n", record)),
+            3
+        );
+        assert_eq!(error_line(&format!("{}foo = 1\n", record)), 5);
+        assert_eq!(error_line(&format!("{}count = 1\n", record)), 5);
+        assert_eq!(error_line(&format!("{}garbage\n", record)), 5);
+    }
+}
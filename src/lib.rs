@@ -1,16 +1,45 @@
+#[cfg(feature = "acvp")]
+pub mod acvp;
 pub mod bitsliced_functionality;
 pub mod bitsliced_arithmetic;
+#[cfg(feature = "cli")]
+pub mod cli;
+#[cfg(feature = "composite")]
+pub mod composite;
 pub mod constants;
 pub mod crypto_primitives;
+pub mod extension_field;
+pub mod ffi;
 pub mod finite_field;
+pub mod kat;
+#[cfg(feature = "pkcs8")]
+pub mod key_encoding;
+pub mod linear_algebra;
+pub mod matrix;
 pub mod mayo_functionality;
+pub mod message_hasher;
+#[cfg(feature = "bench")]
 pub mod benchmark;
+#[cfg(feature = "python")]
+pub mod python;
 pub mod write_and_compare_kat_file;
 pub mod sample;
+#[cfg(feature = "signature")]
+pub mod signature_traits;
+#[cfg(feature = "trace")]
+pub mod trace;
 pub mod utils;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "x509")]
+pub mod x509;
+#[cfg(mayo_portable)]
+pub mod portable;
 pub mod genkat {
+    #[cfg(not(mayo_portable))]
     pub mod bindings;
 }
 pub mod arm_neon_intrinsic {
+    #[cfg(not(mayo_portable))]
     pub mod arm_intrinsic;
 }
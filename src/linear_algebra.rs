@@ -0,0 +1,484 @@
+// Linear algebra over GF(16) for matrices of any size: echelon forms, rank, kernel, inverse,
+// determinant and the complete solution set of Ax = b. The matrices are heap backed, as the
+// systems of interest (e.g. for cryptanalysis) are larger than what fits on the stack.
+use std::ops::{Index, IndexMut, Mul};
+
+use crate::finite_field::Gf16;
+use crate::matrix::Matrix;
+
+// MAYO Algorithm 1 on rows of equal length: row echelon form with leading ones, eliminating only
+// below the pivots. Returns the pivot column of every nonzero row.
+pub fn row_echelon<T: AsMut<[Gf16]>>(rows: &mut [T]) -> Vec<usize> {
+    let cols = rows.first_mut().map_or(0, |row| row.as_mut().len());
+    let mut pivots = Vec::new();
+    let mut pivot_row = 0;
+    let mut pivot_column = 0;
+
+    while pivot_row < rows.len() && pivot_column < cols {
+        // Find the first possible pivot in the current column
+        let Some(next_pivot_row) =
+            (pivot_row..rows.len()).find(|&i| !rows[i].as_mut()[pivot_column].is_zero())
+        else {
+            pivot_column += 1; // Move to next column if there is no pivot
+            continue;
+        };
+        swap_rows(rows, pivot_row, next_pivot_row);
+
+        // Make the leading entry a "1" by multiplying the row by the inverse of the pivot
+        let (upper, lower) = rows.split_at_mut(pivot_row + 1);
+        let pivot = upper[pivot_row].as_mut();
+        let inv_pivot = pivot[pivot_column].inv();
+        for element in &mut pivot[pivot_column..] {
+            *element *= inv_pivot;
+        }
+
+        // Eliminate entries below the pivot
+        for row in lower {
+            let row = row.as_mut();
+            let factor = row[pivot_column];
+            sub_scaled(&mut row[pivot_column..], &pivot[pivot_column..], factor);
+        }
+        pivots.push(pivot_column);
+        pivot_row += 1;
+        pivot_column += 1;
+    }
+    pivots
+}
+
+// Swaps the contents, so that this also works on a list of row references
+fn swap_rows<T: AsMut<[Gf16]>>(rows: &mut [T], i: usize, j: usize) {
+    if i != j {
+        let (upper, lower) = rows.split_at_mut(i.max(j));
+        upper[i.min(j)].as_mut().swap_with_slice(lower[0].as_mut());
+    }
+}
+
+// row = row - factor * other
+fn sub_scaled(row: &mut [Gf16], other: &[Gf16], factor: Gf16) {
+    for (element, other) in row.iter_mut().zip(other) {
+        *element -= factor * *other;
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DynMatrix {
+    rows: usize,
+    cols: usize,
+    elements: Vec<Gf16>, // Row by row
+}
+
+// All solutions of Ax = b: the particular solution plus any combination of the kernel basis.
+// There are 16^dimension() of them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Solutions {
+    pub particular: Vec<Gf16>,
+    pub kernel: Vec<Vec<Gf16>>,
+}
+
+impl DynMatrix {
+    pub fn zero(rows: usize, cols: usize) -> Self {
+        DynMatrix {
+            rows,
+            cols,
+            elements: vec![Gf16::ZERO; rows * cols],
+        }
+    }
+
+    pub fn identity(n: usize) -> Self {
+        let mut identity = Self::zero(n, n);
+        for i in 0..n {
+            identity[(i, i)] = Gf16::ONE;
+        }
+        identity
+    }
+
+    pub fn from_elements(
+        rows: usize,
+        cols: usize,
+        elements: Vec<Gf16>,
+    ) -> Result<Self, &'static str> {
+        if elements.len() != rows * cols {
+            return Err("Number of elements does not match the dimensions");
+        }
+        Ok(DynMatrix {
+            rows,
+            cols,
+            elements,
+        })
+    }
+
+    pub fn from_nibbles(rows: usize, cols: usize, values: &[u8]) -> Result<Self, &'static str> {
+        let elements = values
+            .iter()
+            .map(|value| Gf16::new(*value))
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::from_elements(rows, cols, elements)
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn row(&self, i: usize) -> &[Gf16] {
+        &self.elements[i * self.cols..(i + 1) * self.cols]
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut transposed = Self::zero(self.cols, self.rows);
+        for i in 0..self.rows {
+            for j in 0..self.cols {
+                transposed[(j, i)] = self[(i, j)];
+            }
+        }
+        transposed
+    }
+
+    pub fn mul_vec(&self, x: &[Gf16]) -> Vec<Gf16> {
+        assert_eq!(
+            x.len(),
+            self.cols,
+            "Vector length does not match the matrix"
+        );
+        (0..self.rows)
+            .map(|i| self.row(i).iter().zip(x).map(|(a, x)| *a * *x).sum())
+            .collect()
+    }
+
+    // [self | other]
+    fn augment(&self, other: &DynMatrix) -> Self {
+        assert_eq!(
+            self.rows, other.rows,
+            "Matrices have different numbers of rows"
+        );
+        let mut augmented = Self::zero(self.rows, self.cols + other.cols);
+        for i in 0..self.rows {
+            let row = &mut augmented.elements[i * (self.cols + other.cols)..];
+            row[..self.cols].copy_from_slice(self.row(i));
+            row[self.cols..self.cols + other.cols].copy_from_slice(other.row(i));
+        }
+        augmented
+    }
+
+    fn rows_mut(&mut self) -> Vec<&mut [Gf16]> {
+        self.elements.chunks_mut(self.cols.max(1)).collect()
+    }
+
+    // Row echelon form with leading ones (as `row_echelon`) and the pivot columns
+    pub fn echelon_form(&self) -> (Self, Vec<usize>) {
+        let mut echelon = self.clone();
+        let pivots = row_echelon(&mut echelon.rows_mut());
+        (echelon, pivots)
+    }
+
+    // Reduced row echelon form (zero above and below every pivot) and the pivot columns
+    pub fn reduced_echelon_form(&self) -> (Self, Vec<usize>) {
+        let (mut echelon, pivots) = self.echelon_form();
+        let mut rows = echelon.rows_mut();
+        for (pivot_row, &pivot_column) in pivots.iter().enumerate().rev() {
+            let (upper, lower) = rows.split_at_mut(pivot_row);
+            let pivot = &lower[0][pivot_column..];
+            for row in upper {
+                let factor = row[pivot_column];
+                sub_scaled(&mut row[pivot_column..], pivot, factor);
+            }
+        }
+        (echelon, pivots)
+    }
+
+    pub fn rank(&self) -> usize {
+        self.echelon_form().1.len()
+    }
+
+    // A basis of {x : Ax = 0}, one vector per non-pivot column
+    pub fn kernel(&self) -> Vec<Vec<Gf16>> {
+        let (reduced, pivots) = self.reduced_echelon_form();
+        (0..self.cols)
+            .filter(|column| !pivots.contains(column))
+            .map(|free| {
+                let mut x = vec![Gf16::ZERO; self.cols];
+                x[free] = Gf16::ONE;
+                for (pivot_row, &pivot_column) in pivots.iter().enumerate() {
+                    x[pivot_column] = -reduced[(pivot_row, free)];
+                }
+                x
+            })
+            .collect()
+    }
+
+    pub fn inverse(&self) -> Result<Self, &'static str> {
+        if self.rows != self.cols {
+            return Err("Matrix is not square");
+        }
+        let n = self.rows;
+        let (reduced, pivots) = self.augment(&Self::identity(n)).reduced_echelon_form();
+        if (0..n).any(|i| pivots.get(i) != Some(&i)) {
+            return Err("Matrix is not invertible");
+        }
+
+        let mut inverse = Self::zero(n, n);
+        for i in 0..n {
+            inverse.elements[i * n..(i + 1) * n].copy_from_slice(&reduced.row(i)[n..]);
+        }
+        Ok(inverse)
+    }
+
+    // Gaussian elimination without scaling the rows. Swapping rows does not change the sign in
+    // characteristic 2, so the determinant is the product of the pivots.
+    pub fn determinant(&self) -> Result<Gf16, &'static str> {
+        if self.rows != self.cols {
+            return Err("Matrix is not square");
+        }
+        let mut matrix = self.clone();
+        let mut rows = matrix.rows_mut();
+        let mut determinant = Gf16::ONE;
+        for column in 0..rows.len() {
+            let Some(pivot_row) = (column..rows.len()).find(|&i| !rows[i][column].is_zero()) else {
+                return Ok(Gf16::ZERO);
+            };
+            swap_rows(&mut rows, column, pivot_row);
+
+            let (upper, lower) = rows.split_at_mut(column + 1);
+            let pivot = &upper[column][column..];
+            let inv_pivot = pivot[0].inv();
+            determinant *= pivot[0];
+            for row in lower {
+                let factor = row[column] * inv_pivot;
+                sub_scaled(&mut row[column..], pivot, factor);
+            }
+        }
+        Ok(determinant)
+    }
+
+    pub fn solve(&self, b: &[Gf16]) -> Result<Solutions, &'static str> {
+        if b.len() != self.rows {
+            return Err("Right-hand side has incorrect length");
+        }
+        let b = Self::from_elements(self.rows, 1, b.to_vec())?;
+        let (reduced, pivots) = self.augment(&b).reduced_echelon_form();
+        if pivots.last() == Some(&self.cols) {
+            return Err("The system has no solution");
+        }
+
+        // Free variables are zero in the particular solution
+        let mut particular = vec![Gf16::ZERO; self.cols];
+        for (pivot_row, &pivot_column) in pivots.iter().enumerate() {
+            particular[pivot_column] = reduced[(pivot_row, self.cols)];
+        }
+        Ok(Solutions {
+            particular,
+            kernel: self.kernel(),
+        })
+    }
+}
+
+impl Solutions {
+    pub fn dimension(&self) -> usize {
+        self.kernel.len()
+    }
+
+    // particular + sum coefficients[i] * kernel[i]
+    pub fn solution(&self, coefficients: &[Gf16]) -> Vec<Gf16> {
+        assert_eq!(
+            coefficients.len(),
+            self.dimension(),
+            "One coefficient per kernel vector"
+        );
+        let mut x = self.particular.clone();
+        for (coefficient, kernel) in coefficients.iter().zip(&self.kernel) {
+            sub_scaled(&mut x, kernel, -*coefficient);
+        }
+        x
+    }
+
+    // Every solution, in the order of the coefficients as base-16 digits. Only practical for a
+    // small dimension.
+    pub fn iter(&self) -> impl Iterator<Item = Vec<Gf16>> + '_ {
+        let count = 16u128
+            .checked_pow(self.dimension() as u32)
+            .expect("Too many solutions to enumerate");
+        (0..count).map(move |index| {
+            let coefficients: Vec<Gf16> = (0..self.dimension())
+                .map(|i| Gf16::from_nibble(((index >> (4 * i)) & 0xf) as u8))
+                .collect();
+            self.solution(&coefficients)
+        })
+    }
+}
+
+impl Index<(usize, usize)> for DynMatrix {
+    type Output = Gf16;
+
+    fn index(&self, (i, j): (usize, usize)) -> &Gf16 {
+        assert!(i < self.rows && j < self.cols, "Index out of bounds");
+        &self.elements[i * self.cols + j]
+    }
+}
+
+impl IndexMut<(usize, usize)> for DynMatrix {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut Gf16 {
+        assert!(i < self.rows && j < self.cols, "Index out of bounds");
+        &mut self.elements[i * self.cols + j]
+    }
+}
+
+impl Mul for &DynMatrix {
+    type Output = DynMatrix;
+
+    fn mul(self, rhs: &DynMatrix) -> DynMatrix {
+        assert_eq!(self.cols, rhs.rows, "Matrix dimensions do not match");
+        let mut product = DynMatrix::zero(self.rows, rhs.cols);
+        for i in 0..self.rows {
+            for (k, element) in self.row(i).iter().enumerate() {
+                let row = &mut product.elements[i * rhs.cols..(i + 1) * rhs.cols];
+                sub_scaled(row, rhs.row(k), -*element);
+            }
+        }
+        product
+    }
+}
+
+impl<const R: usize, const C: usize> From<&Matrix<R, C>> for DynMatrix {
+    fn from(matrix: &Matrix<R, C>) -> Self {
+        DynMatrix {
+            rows: R,
+            cols: C,
+            elements: matrix.0.concat(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{K, M, O};
+    use crate::matrix::Vector;
+    use crate::sample::sample_solution;
+    use proptest::prelude::*;
+
+    fn matrix_of(rows: usize, cols: usize) -> impl Strategy<Value = DynMatrix> {
+        prop::collection::vec(0u8..16, rows * cols)
+            .prop_map(move |values| DynMatrix::from_nibbles(rows, cols, &values).unwrap())
+    }
+
+    // Matrices with 1..=rows_max rows and 1..=cols_max columns
+    fn matrix(rows_max: usize, cols_max: usize) -> impl Strategy<Value = DynMatrix> {
+        (1..=rows_max, 1..=cols_max).prop_flat_map(|(rows, cols)| matrix_of(rows, cols))
+    }
+
+    fn square_matrices(n_max: usize) -> impl Strategy<Value = (DynMatrix, DynMatrix)> {
+        (1..=n_max).prop_flat_map(|n| (matrix_of(n, n), matrix_of(n, n)))
+    }
+
+    // Matrices of rank at most `rank` as products of (rows x rank) and (rank x cols)
+    fn low_rank_matrix() -> impl Strategy<Value = DynMatrix> {
+        (1..12usize, 1..12usize, 1..6usize).prop_flat_map(|(rows, cols, rank)| {
+            (
+                prop::collection::vec(0u8..16, rows * rank),
+                prop::collection::vec(0u8..16, rank * cols),
+            )
+                .prop_map(move |(a, b)| {
+                    let a = DynMatrix::from_nibbles(rows, rank, &a).unwrap();
+                    let b = DynMatrix::from_nibbles(rank, cols, &b).unwrap();
+                    &a * &b
+                })
+        })
+    }
+
+    fn elements(len: usize) -> impl Strategy<Value = Vec<Gf16>> {
+        prop::collection::vec((0u8..16).prop_map(Gf16::from_nibble), len)
+    }
+
+    fn is_zero(x: &[Gf16]) -> bool {
+        x.iter().all(|element| element.is_zero())
+    }
+
+    #[test]
+    fn test_small_examples() {
+        let a = DynMatrix::from_nibbles(2, 2, &[0x1, 0x2, 0x3, 0x4]).unwrap();
+        // det = 1 * 4 - 2 * 3 = x^2 + (x^2 + x) = x
+        assert_eq!(a.determinant(), Ok(Gf16::new(0x2).unwrap()));
+        assert_eq!(&a * &a.inverse().unwrap(), DynMatrix::identity(2));
+
+        let singular = DynMatrix::from_nibbles(2, 3, &[0x1, 0x2, 0x3, 0x2, 0x4, 0x6]).unwrap();
+        assert_eq!(singular.rank(), 1);
+        assert_eq!(singular.kernel().len(), 2);
+        let b = [Gf16::ONE, Gf16::ZERO];
+        assert!(singular.solve(&b).is_err());
+        assert!(DynMatrix::from_nibbles(1, 2, &[0x1, 0x10]).is_err());
+        assert!(singular.inverse().is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn test_rank_nullity(a in prop_oneof![matrix(16, 16), low_rank_matrix()]) {
+            let kernel = a.kernel();
+            prop_assert_eq!(a.rank() + kernel.len(), a.cols());
+            prop_assert_eq!(a.rank(), a.transpose().rank());
+            for x in &kernel {
+                prop_assert!(is_zero(&a.mul_vec(x)));
+            }
+            // The kernel vectors are independent
+            if !kernel.is_empty() {
+                let basis = DynMatrix::from_elements(kernel.len(), a.cols(), kernel.concat()).unwrap();
+                prop_assert_eq!(basis.rank(), kernel.len());
+            }
+        }
+
+        #[test]
+        fn test_inverse((a, b) in square_matrices(12)) {
+            let n = a.rows();
+            let determinant = a.determinant().unwrap();
+            match a.inverse() {
+                Ok(inverse) => {
+                    prop_assert!(!determinant.is_zero());
+                    prop_assert_eq!(&a * &inverse, DynMatrix::identity(n));
+                    prop_assert_eq!(&inverse * &a, DynMatrix::identity(n));
+                    prop_assert_eq!(inverse.determinant().unwrap(), determinant.inv());
+                }
+                Err(_) => {
+                    prop_assert!(determinant.is_zero());
+                    prop_assert!(a.rank() < n);
+                }
+            }
+
+            // det(AB) = det(A) det(B)
+            prop_assert_eq!((&a * &b).determinant().unwrap(), determinant * b.determinant().unwrap());
+        }
+
+        #[test]
+        fn test_solve(a in prop_oneof![matrix(12, 12), low_rank_matrix()], x in elements(12)) {
+            // b in the image of A has a solution, and every solution maps to b
+            let b = a.mul_vec(&x[..a.cols()]);
+            let solutions = a.solve(&b).unwrap();
+            prop_assert_eq!(solutions.dimension(), a.cols() - a.rank());
+            prop_assert_eq!(a.mul_vec(&solutions.particular), b.clone());
+            let coefficients = vec![Gf16::ONE; solutions.dimension()];
+            prop_assert_eq!(a.mul_vec(&solutions.solution(&coefficients)), b.clone());
+            if solutions.dimension() <= 2 {
+                let all: Vec<_> = solutions.iter().collect();
+                prop_assert_eq!(all.len(), 1 << (4 * solutions.dimension()));
+                prop_assert!(all.contains(&x[..a.cols()].to_vec()));
+            }
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(16))]
+
+        // MAYO's sample_solution finds one of the solutions if A has full rank
+        #[test]
+        fn test_sample_solution(a in matrix_of(M, K * O), y in elements(M), r in elements(K * O)) {
+            let matrix = Matrix::<M, { K * O }>(std::array::from_fn(|i| a.row(i).try_into().unwrap()));
+            let y_vector = Vector(y.clone().try_into().unwrap());
+            match sample_solution(matrix, y_vector, Vector(r.try_into().unwrap())) {
+                Ok(x) => prop_assert_eq!(a.mul_vec(&x.0), y),
+                Err(_) => prop_assert!(a.rank() < M),
+            }
+            prop_assert_eq!(DynMatrix::from(&matrix), a);
+        }
+    }
+}
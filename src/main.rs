@@ -1,10 +1,23 @@
 fn main() {
+    // With arguments, run the command line tool (feature `cli`, see `lib::cli`)
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        #[cfg(feature = "cli")]
+        std::process::exit(lib::cli::run(&args));
 
+        #[cfg(not(feature = "cli"))]
+        {
+            eprintln!("The command line tool requires the `cli` feature");
+            std::process::exit(2);
+        }
+    }
 
     #[cfg(not(feature = "bench"))]
     {
         use lib::write_and_compare_kat_file::write_and_compare_kat_file;
-        write_and_compare_kat_file();
+        if !write_and_compare_kat_file() {
+            std::process::exit(1);
+        }
     }
 
 
@@ -15,4 +28,4 @@ fn main() {
     }
 
 
-}
\ No newline at end of file
+}
@@ -0,0 +1,341 @@
+// Matrices and vectors over GF(16) with dimensions fixed at compile time. They replace the
+// unpacked [[u8; C]; R] arrays and the matrix macros of finite_field.rs where elements are not
+// bitsliced. Vectors are columns, except on the left of a matrix product (v^t * A).
+use std::ops::{Add, AddAssign, Index, IndexMut, Mul, Sub, SubAssign};
+
+use crate::finite_field::Gf16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Matrix<const R: usize, const C: usize>(pub [[Gf16; C]; R]);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Vector<const N: usize>(pub [Gf16; N]);
+
+impl<const R: usize, const C: usize> Matrix<R, C> {
+    pub fn zero() -> Self {
+        Matrix([[Gf16::ZERO; C]; R])
+    }
+
+    // Elements below 16 by construction, e.g. decoded from packed nibbles (see `Gf16::from_nibble`)
+    pub fn from_nibbles(rows: &[[u8; C]; R]) -> Self {
+        let mut matrix = Self::zero();
+        for (row, nibbles) in matrix.0.iter_mut().zip(rows) {
+            *row = Vector::from_nibbles(nibbles).0;
+        }
+        matrix
+    }
+
+    pub fn to_nibbles(&self) -> [[u8; C]; R] {
+        let mut rows = [[0u8; C]; R];
+        for (nibbles, row) in rows.iter_mut().zip(&self.0) {
+            *nibbles = Vector(*row).to_nibbles();
+        }
+        rows
+    }
+
+    pub fn transpose(&self) -> Matrix<C, R> {
+        let mut transposed = Matrix::zero();
+        for i in 0..R {
+            for j in 0..C {
+                transposed.0[j][i] = self.0[i][j];
+            }
+        }
+        transposed
+    }
+
+    pub fn from_columns(columns: &[Vector<R>; C]) -> Self {
+        let mut matrix = Self::zero();
+        for (j, column) in columns.iter().enumerate() {
+            for (row, element) in matrix.0.iter_mut().zip(column.iter()) {
+                row[j] = *element;
+            }
+        }
+        matrix
+    }
+
+    pub fn column(&self, j: usize) -> Vector<R> {
+        let mut column = Vector::zero();
+        for (element, row) in column.0.iter_mut().zip(&self.0) {
+            *element = row[j];
+        }
+        column
+    }
+
+    pub fn swap_rows(&mut self, i: usize, j: usize) {
+        self.0.swap(i, j);
+    }
+}
+
+impl<const N: usize> Matrix<N, N> {
+    pub fn identity() -> Self {
+        let mut identity = Self::zero();
+        for i in 0..N {
+            identity.0[i][i] = Gf16::ONE;
+        }
+        identity
+    }
+}
+
+impl<const R: usize, const C: usize> TryFrom<[[u8; C]; R]> for Matrix<R, C> {
+    type Error = &'static str;
+
+    fn try_from(rows: [[u8; C]; R]) -> Result<Self, Self::Error> {
+        let mut matrix = Self::zero();
+        for (row, values) in matrix.0.iter_mut().zip(rows) {
+            *row = Vector::try_from(values)?.0;
+        }
+        Ok(matrix)
+    }
+}
+
+impl<const R: usize, const C: usize> Index<usize> for Matrix<R, C> {
+    type Output = [Gf16; C];
+
+    #[inline]
+    fn index(&self, i: usize) -> &[Gf16; C] {
+        &self.0[i]
+    }
+}
+
+impl<const R: usize, const C: usize> IndexMut<usize> for Matrix<R, C> {
+    #[inline]
+    fn index_mut(&mut self, i: usize) -> &mut [Gf16; C] {
+        &mut self.0[i]
+    }
+}
+
+impl<const R: usize, const C: usize> AddAssign for Matrix<R, C> {
+    fn add_assign(&mut self, rhs: Self) {
+        for (row, rhs_row) in self.0.iter_mut().zip(rhs.0) {
+            for (element, rhs_element) in row.iter_mut().zip(rhs_row) {
+                *element += rhs_element;
+            }
+        }
+    }
+}
+
+impl<const R: usize, const C: usize> SubAssign for Matrix<R, C> {
+    fn sub_assign(&mut self, rhs: Self) {
+        for (row, rhs_row) in self.0.iter_mut().zip(rhs.0) {
+            for (element, rhs_element) in row.iter_mut().zip(rhs_row) {
+                *element -= rhs_element;
+            }
+        }
+    }
+}
+
+impl<const R: usize, const C: usize> Add for Matrix<R, C> {
+    type Output = Self;
+
+    fn add(mut self, rhs: Self) -> Self {
+        self += rhs;
+        self
+    }
+}
+
+impl<const R: usize, const C: usize> Sub for Matrix<R, C> {
+    type Output = Self;
+
+    fn sub(mut self, rhs: Self) -> Self {
+        self -= rhs;
+        self
+    }
+}
+
+// (R x C) * (C x K) = (R x K)
+impl<const R: usize, const C: usize, const K: usize> Mul<Matrix<C, K>> for Matrix<R, C> {
+    type Output = Matrix<R, K>;
+
+    fn mul(self, rhs: Matrix<C, K>) -> Matrix<R, K> {
+        let mut product = Matrix::zero();
+        for (product_row, row) in product.0.iter_mut().zip(&self.0) {
+            for (element, rhs_row) in row.iter().zip(&rhs.0) {
+                for (product_element, rhs_element) in product_row.iter_mut().zip(rhs_row) {
+                    *product_element += *element * *rhs_element;
+                }
+            }
+        }
+        product
+    }
+}
+
+// (R x C) * (C x 1) = (R x 1)
+impl<const R: usize, const C: usize> Mul<Vector<C>> for Matrix<R, C> {
+    type Output = Vector<R>;
+
+    fn mul(self, rhs: Vector<C>) -> Vector<R> {
+        let mut product = Vector::zero();
+        for (product_element, row) in product.0.iter_mut().zip(&self.0) {
+            *product_element = Vector(*row).dot(&rhs);
+        }
+        product
+    }
+}
+
+impl<const N: usize> Vector<N> {
+    pub fn zero() -> Self {
+        Vector([Gf16::ZERO; N])
+    }
+
+    pub fn from_nibbles(nibbles: &[u8; N]) -> Self {
+        let mut vector = Self::zero();
+        for (element, nibble) in vector.0.iter_mut().zip(nibbles) {
+            *element = Gf16::from_nibble(*nibble);
+        }
+        vector
+    }
+
+    pub fn to_nibbles(&self) -> [u8; N] {
+        self.0.map(u8::from)
+    }
+
+    pub fn dot(&self, rhs: &Self) -> Gf16 {
+        self.0.iter().zip(&rhs.0).map(|(x, y)| *x * *y).sum()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Gf16> {
+        self.0.iter()
+    }
+}
+
+impl<const N: usize> TryFrom<[u8; N]> for Vector<N> {
+    type Error = &'static str;
+
+    fn try_from(values: [u8; N]) -> Result<Self, Self::Error> {
+        let mut vector = Self::zero();
+        for (element, value) in vector.0.iter_mut().zip(values) {
+            *element = Gf16::new(value)?;
+        }
+        Ok(vector)
+    }
+}
+
+impl<const N: usize> Index<usize> for Vector<N> {
+    type Output = Gf16;
+
+    #[inline]
+    fn index(&self, i: usize) -> &Gf16 {
+        &self.0[i]
+    }
+}
+
+impl<const N: usize> IndexMut<usize> for Vector<N> {
+    #[inline]
+    fn index_mut(&mut self, i: usize) -> &mut Gf16 {
+        &mut self.0[i]
+    }
+}
+
+impl<const N: usize> AddAssign for Vector<N> {
+    fn add_assign(&mut self, rhs: Self) {
+        for (element, rhs_element) in self.0.iter_mut().zip(rhs.0) {
+            *element += rhs_element;
+        }
+    }
+}
+
+impl<const N: usize> SubAssign for Vector<N> {
+    fn sub_assign(&mut self, rhs: Self) {
+        for (element, rhs_element) in self.0.iter_mut().zip(rhs.0) {
+            *element -= rhs_element;
+        }
+    }
+}
+
+impl<const N: usize> Add for Vector<N> {
+    type Output = Self;
+
+    fn add(mut self, rhs: Self) -> Self {
+        self += rhs;
+        self
+    }
+}
+
+impl<const N: usize> Sub for Vector<N> {
+    type Output = Self;
+
+    fn sub(mut self, rhs: Self) -> Self {
+        self -= rhs;
+        self
+    }
+}
+
+// Scalar multiplication
+impl<const N: usize> Mul<Gf16> for Vector<N> {
+    type Output = Self;
+
+    fn mul(mut self, rhs: Gf16) -> Self {
+        for element in self.0.iter_mut() {
+            *element *= rhs;
+        }
+        self
+    }
+}
+
+// (1 x N) * (N x C) = (1 x C)
+impl<const N: usize, const C: usize> Mul<Matrix<N, C>> for Vector<N> {
+    type Output = Vector<C>;
+
+    fn mul(self, rhs: Matrix<N, C>) -> Vector<C> {
+        let mut product = Vector::zero();
+        for (element, row) in self.0.iter().zip(&rhs.0) {
+            product += Vector(*row) * *element;
+        }
+        product
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::finite_field::{add, mul};
+    use crate::{matrix_mul, matrix_vec_mul, transpose_matrix_array, vector_matrix_mul};
+
+    const A: [[u8; 3]; 2] = [[0x1, 0x7, 0xc], [0x0, 0xf, 0x2]];
+    const B: [[u8; 2]; 3] = [[0x3, 0x9], [0xa, 0x0], [0x5, 0xe]];
+    const X: [u8; 3] = [0xb, 0x4, 0x6];
+    const Y: [u8; 2] = [0x8, 0xd];
+
+    #[test]
+    fn test_products_match_macros() {
+        let a = Matrix::try_from(A).unwrap();
+        let b = Matrix::try_from(B).unwrap();
+
+        assert_eq!((a * b).to_nibbles(), matrix_mul!(A, 2, 3, B, 2));
+        assert_eq!(
+            (a * Vector(X.map(Gf16::from_nibble))).to_nibbles(),
+            matrix_vec_mul!(A, X, 2, 3)
+        );
+        assert_eq!(
+            (Vector::try_from(Y).unwrap() * a).to_nibbles(),
+            vector_matrix_mul!(Y, A, 2, 3)
+        );
+        assert_eq!(a.transpose().to_nibbles(), transpose_matrix_array!(A, 2, 3));
+    }
+
+    #[test]
+    fn test_identities() {
+        let a = Matrix::try_from(A).unwrap();
+        let b = Matrix::try_from(B).unwrap();
+
+        assert_eq!(Matrix::identity() * a, a);
+        assert_eq!(a * Matrix::identity(), a);
+        assert_eq!((a * b).transpose(), b.transpose() * a.transpose());
+        assert_eq!(a - a, Matrix::zero());
+        assert_eq!(a.column(1), Vector(a.transpose()[1]));
+        assert_eq!(Matrix::from_columns(&[0, 1, 2].map(|j| a.column(j))), a);
+    }
+
+    #[test]
+    fn test_rejects_values_outside_the_field() {
+        assert!(Matrix::try_from([[0x1, 0x10]]).is_err());
+        assert!(Vector::try_from([0x0, 0x2, 0xff]).is_err());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic]
+    fn test_from_nibbles_checks_the_range_in_debug_builds() {
+        Matrix::from_nibbles(&[[0x1, 0x10]]);
+    }
+}
@@ -1,29 +1,62 @@
+use std::collections::HashMap;
 use std::vec;
 
-use crate::bitsliced_arithmetic::{calculate_st_p, p1_add_p1t};
+use crate::bitsliced_arithmetic::{calculate_st_p, mul_add_bitsliced_m_vec, p1_add_p1t};
 use crate::constants::{
-    CSK_BYTES, DIGEST_BYTES, F_Z, K, L_BYTES, M, N, O, O_BYTES, P1_BYTES, P2_BYTES, P3_BYTES,
-    PK_SEED_BYTES, R_BYTES, SALT_BYTES, SHIFTS, SIG_BYTES, SK_SEED_BYTES, V, V_BYTES,
+    CPK_BYTES, CSK_BYTES, DIGEST_BYTES, K, L_BYTES, M, N, O, O_BYTES, P1_BYTES, P2_BYTES, P3_BYTES,
+    PK_SEED_BYTES, R_BYTES, SALT_BYTES, SIG_BYTES, SK_SEED_BYTES, V, V_BYTES,
+};
+use crate::crypto_primitives::{
+    safe_aes_128_ctr, safe_aes_128_ctr_offset, safe_random_bytes, safe_shake256,
 };
-use crate::crypto_primitives::{safe_aes_128_ctr, safe_random_bytes, safe_shake256};
 #[allow(unused_imports)]
 use crate::crypto_primitives::{
     safe_mul_add_bitsliced_m_vec_mayo12, safe_mul_add_bitsliced_m_vec_mayo3,
     safe_mul_add_bitsliced_m_vec_mayo5,
 };
-use crate::finite_field::{add, mul};
-use crate::sample::sample_solution;
+use crate::extension_field::{BitslicedElement, ExtensionElement};
+use crate::matrix::{Matrix, Vector};
+use crate::message_hasher::MessageHasher;
+#[cfg(feature = "trace")]
+use crate::sample::unpack_rows;
+use crate::sample::{sample_solution_packed, PackedRows, WORDS};
 
 use crate::{
-    bitsliced_mat_mul_mat_add, bitsliced_mat_mul_transposed_mat_add, decode_bit_sliced_array,
+    bitsliced_mat_mul_mat_add, bitsliced_mat_mul_transposed_mat_add,
     decode_bytestring_matrix_array, decode_bytestring_to_array, encode_to_bytestring_array,
-    mat_mul_bitsliced_mat_add, matrix_vec_mul, transposed_mat_mul_bitsliced_mat_add, upper,
-    vec_add,
+    mat_mul_bitsliced_mat_add, transposed_mat_mul_bitsliced_mat_add, upper,
 };
 
 const U32_PER_IDX: usize = M / 4 / 2;
 
+// Records a named intermediate value in the active trace (feature `trace`, see crate::trace).
+// Field elements are packed two per byte, bitsliced m-vectors are written as their elements.
+#[cfg(feature = "trace")]
+macro_rules! trace_value {
+    ($name:expr, elements $value:expr) => {
+        if crate::trace::is_active() {
+            crate::trace::record($name, crate::trace::encode_elements(&$value));
+        }
+    };
+    ($name:expr, m_vectors $value:expr) => {
+        if crate::trace::is_active() {
+            crate::trace::record($name, crate::trace::encode_m_vectors(&$value));
+        }
+    };
+    ($name:expr, bytes $value:expr) => {
+        if crate::trace::is_active() {
+            crate::trace::record($name, $value.to_vec());
+        }
+    };
+}
+
+#[cfg(not(feature = "trace"))]
+macro_rules! trace_value {
+    ($($tokens:tt)*) => {};
+}
+
 pub struct ExpandedSecretKey {
+    sk_seed: [u8; SK_SEED_BYTES],
     p1: [u32; P1_BYTES / 4],
     l: [u32; L_BYTES / 4],
     o: [u8; O_BYTES],
@@ -35,17 +68,54 @@ pub struct ExpandedPublicKey {
     p3: [u32; P3_BYTES / 4],
 }
 
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct CompactPublicKey {
     pub seed: [u8; 16],
     pub p3: [u32; P3_BYTES / 4],
 }
 
+impl CompactPublicKey {
+    // Serialize as pk_seed || P3 (P3 as little endian u32s), the same layout as the NIST KAT files
+    pub fn to_bytes(&self) -> [u8; CPK_BYTES] {
+        let mut bytes = [0u8; CPK_BYTES];
+        bytes[..PK_SEED_BYTES].copy_from_slice(&self.seed);
+        for (i, word) in self.p3.iter().enumerate() {
+            let start = PK_SEED_BYTES + i * 4;
+            bytes[start..start + 4].copy_from_slice(&word.to_le_bytes());
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, &'static str> {
+        if bytes.len() != CPK_BYTES {
+            return Err("The public key has incorrect length");
+        }
+
+        let mut seed = [0u8; PK_SEED_BYTES];
+        seed.copy_from_slice(&bytes[..PK_SEED_BYTES]);
+
+        let mut p3 = [0u32; P3_BYTES / 4];
+        for (i, word) in bytes[PK_SEED_BYTES..].chunks(4).enumerate() {
+            p3[i] = u32::from_le_bytes(word.try_into().expect("Slice has incorrect length"));
+        }
+
+        Ok(CompactPublicKey { seed, p3 })
+    }
+}
+
 // MAYO algorithm 5:
 pub fn compact_key_gen() -> (CompactPublicKey, [u8; CSK_BYTES]) {
     // Pick random seed_sk at random (using NIST randomness source)
     let mut sk_seed = [0u8; SK_SEED_BYTES];
     safe_random_bytes(&mut sk_seed, SK_SEED_BYTES as u64);
 
+    compact_key_gen_from_seed(sk_seed)
+}
+
+// MAYO algorithm 5 from a given seed_sk. Also recovers the public key belonging to a compact secret key.
+pub fn compact_key_gen_from_seed(
+    sk_seed: [u8; SK_SEED_BYTES],
+) -> (CompactPublicKey, [u8; CSK_BYTES]) {
     // Derive pk_seed and Oil space O from sk_seed
     let mut s = [0u8; PK_SEED_BYTES + O_BYTES];
     safe_shake256(
@@ -64,6 +134,8 @@ pub fn compact_key_gen() -> (CompactPublicKey, [u8; CSK_BYTES]) {
     // Make Oil space from o_bytes. Only a single is yielded from decode_bit_sliced_matrices in this case
     let o_bytes = &s[PK_SEED_BYTES..PK_SEED_BYTES + O_BYTES];
     let o = decode_bytestring_matrix_array!(o_bytes, V, O);
+    trace_value!("pk_seed", bytes pk_seed);
+    trace_value!("O", bytes o_bytes);
 
     // Derive P1_i and P2_i from pk_seed
     let mut p = [0u32; (P1_BYTES + P2_BYTES) / 4];
@@ -87,6 +159,7 @@ pub fn compact_key_gen() -> (CompactPublicKey, [u8; CSK_BYTES]) {
     // Compute upper of p3
     let mut p3_upper = [0u32; P3_BYTES / 4];
     upper!(&mut p3, &mut p3_upper, V, O);
+    trace_value!("P3", m_vectors p3_upper);
 
     // Public and secret keys
     let cpk = CompactPublicKey {
@@ -122,12 +195,14 @@ pub fn expand_sk(csk: [u8; CSK_BYTES]) -> ExpandedSecretKey {
     // Make Oil space from o_bytes
     let o_bytes = &s[PK_SEED_BYTES..PK_SEED_BYTES + O_BYTES];
     let o = decode_bytestring_matrix_array!(o_bytes, V, O);
+    trace_value!("O", bytes o_bytes);
 
     // Derive P1_i and P2_i from pk_seed
     let mut p = [0u32; (P1_BYTES + P2_BYTES) / 4];
     safe_aes_128_ctr(&mut p, (P1_BYTES + P2_BYTES) as u64, &pk_seed);
 
     let (p1, p2) = p.split_at_mut(P1_BYTES / 4);
+    trace_value!("P1", m_vectors p1);
 
     // Compute L = (P1 + P1^t)*O + P2
 
@@ -137,11 +212,13 @@ pub fn expand_sk(csk: [u8; CSK_BYTES]) -> ExpandedSecretKey {
 
     // Compute (P1 + P1^t)*O + P2 stored in p2
     bitsliced_mat_mul_mat_add!(p1_p1t_added, o, p2, V, V, O, false); // upper_triangular = false
+    trace_value!("L", m_vectors p2);
 
     // To follow the refference implementation append O_bytestring at the end
-    // Do not add sk_seed to the expanded secret key
+    // sk_seed is kept so that the expanded secret key can be used for signing directly
 
     ExpandedSecretKey {
+        sk_seed,
         p1: p1.try_into().expect("Slice has incorrect length"),
         l: p2.try_into().expect("Slice has incorrect length"),
         o: o_bytes.try_into().expect("Slice has incorrect length"),
@@ -167,34 +244,282 @@ pub fn expand_pk(cpk: CompactPublicKey) -> ExpandedPublicKey {
 // MAYO algorithm 8
 // Signs a message using an expanded secret key
 pub fn sign(compact_secret_key: [u8; CSK_BYTES], message: &[u8]) -> [u8; SIG_BYTES] {
-    let mut x = [0u8; K * O]; // Initialize x to zero
-    let mut v = [[0u8; V]; K]; // Initialize v to zero
+    sign_digest(compact_secret_key, &message_digest(message))
+}
+
+// Signs a message given its digest, e.g. as produced by a `MessageHasher`.
+// Gives the same signature as `sign` on the full message.
+pub fn sign_digest(
+    compact_secret_key: [u8; CSK_BYTES],
+    m_digest: &[u8; DIGEST_BYTES],
+) -> [u8; SIG_BYTES] {
+    // Derive salt randomness
+    let mut r = [0u8; R_BYTES];
+    safe_random_bytes(&mut r, R_BYTES as u64);
 
-    // Unlike specifcation, sk_seed is NOT included ESK
+    sign_digest_with_randomness(compact_secret_key, m_digest, r)
+}
+
+// Signs a message digest with caller-provided randomness for the salt derivation,
+// e.g. from an external RNG. Deterministic given its inputs.
+pub fn sign_digest_with_randomness(
+    compact_secret_key: [u8; CSK_BYTES],
+    m_digest: &[u8; DIGEST_BYTES],
+    r: [u8; R_BYTES],
+) -> [u8; SIG_BYTES] {
     let expanded_sk: ExpandedSecretKey = expand_sk(compact_secret_key);
+    let o = decode_bytestring_matrix_array!(expanded_sk.o, V, O);
 
-    // Decode expanded secret key
-    let p1: [u32; P1_BYTES / 4] = expanded_sk.p1;
-    let l: [u32; L_BYTES / 4] = expanded_sk.l;
-    let o_bytestring: [u8; O_BYTES] = expanded_sk.o;
+    sign_expanded_with_randomness(&expanded_sk, &o, m_digest, r)
+}
 
-    let o = decode_bytestring_matrix_array!(o_bytestring, V, O);
+// Signs every message in `messages` with the same expanded secret key.
+// The key is expanded and O decoded only once, which makes this considerably faster than
+// calling `sign` for each message. With the `rayon` feature the messages are signed in parallel.
+pub fn sign_batch(expanded_sk: &ExpandedSecretKey, messages: &[&[u8]]) -> Vec<[u8; SIG_BYTES]> {
+    let o = decode_bytestring_matrix_array!(expanded_sk.o, V, O);
+
+    // Draw the salt randomness up front, the randomness source is not thread safe
+    let randomness: Vec<[u8; R_BYTES]> = messages
+        .iter()
+        .map(|_| {
+            let mut r = [0u8; R_BYTES];
+            safe_random_bytes(&mut r, R_BYTES as u64);
+            r
+        })
+        .collect();
+
+    let sign_one = |(message, r): (&&[u8], &[u8; R_BYTES])| {
+        sign_expanded_with_randomness(expanded_sk, &o, &message_digest(message), *r)
+    };
+
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        messages
+            .par_iter()
+            .zip(randomness.par_iter())
+            .map(sign_one)
+            .collect()
+    }
 
-    // Hash message
-    let mut m_digest = [0u8; DIGEST_BYTES];
-    safe_shake256(
-        &mut m_digest,
-        DIGEST_BYTES as u64,
-        message,
-        message.len() as u64,
-    );
+    #[cfg(not(feature = "rayon"))]
+    {
+        messages
+            .iter()
+            .zip(randomness.iter())
+            .map(sign_one)
+            .collect()
+    }
+}
 
-    // Derive salt
+// Signs a message digest using an already expanded secret key and decoded Oil space
+fn sign_expanded_with_randomness(
+    expanded_sk: &ExpandedSecretKey,
+    o: &[[u8; O]; V],
+    m_digest: &[u8; DIGEST_BYTES],
+    r: [u8; R_BYTES],
+) -> [u8; SIG_BYTES] {
+    sign_core(expanded_sk.sk_seed, o, m_digest, r, |v| {
+        // Build K matrices of size M x O
+        let mut m_matrices_array = [0u32; K * O * M / 8];
+        mat_mul_bitsliced_mat_add!(v, expanded_sk.l, &mut m_matrices_array, K, V, O);
+
+        // v^t * P1
+        let mut vt_p1 = [0u32; V * K * M / 8];
+        bitsliced_mat_mul_transposed_mat_add!(expanded_sk.p1, v, &mut vt_p1, V, V, K, 0, true);
+
+        // v^t * P1 * v
+        let mut vt_p1_v = [0u32; K * K * M / 8];
+        mat_mul_bitsliced_mat_add!(v, vt_p1, &mut vt_p1_v, K, V, K);
+
+        (m_matrices_array, vt_p1_v)
+    })
+}
+
+// Smallest chunk accepted by `sign_low_memory`: the first and longest rows of P1 and P2
+pub const MIN_CHUNK_BYTES: usize = N * U32_PER_IDX * 4;
+
+// Signs a message without keeping the expanded secret key in memory.
+// P1 and P2 are regenerated from pk_seed for every signing attempt, in chunks of whole rows of at
+// most `chunk_bytes` bytes (row r of P1 together with row r of P2). The rows of the M_i matrices
+// are accumulated from each chunk, so L = (P1 + P1^t)*O + P2 is never materialized: besides the
+// chunk, only O and the arrays of the signing steps are held (see the peak memory columns of
+// benchmark.rs). Chunk sizes below MIN_CHUNK_BYTES are rejected, and sizes above
+// P1_BYTES + P2_BYTES hold all of P1 and P2. The signature is identical to the one produced by
+// `sign`.
+pub fn sign_low_memory(
+    compact_secret_key: [u8; CSK_BYTES],
+    message: &[u8],
+    chunk_bytes: usize,
+) -> Result<[u8; SIG_BYTES], &'static str> {
+    // Derive salt randomness
     let mut r = [0u8; R_BYTES];
     safe_random_bytes(&mut r, R_BYTES as u64);
 
+    sign_low_memory_with_randomness(compact_secret_key, &message_digest(message), chunk_bytes, r)
+}
+
+fn sign_low_memory_with_randomness(
+    compact_secret_key: [u8; CSK_BYTES],
+    m_digest: &[u8; DIGEST_BYTES],
+    chunk_bytes: usize,
+    r: [u8; R_BYTES],
+) -> Result<[u8; SIG_BYTES], &'static str> {
+    if chunk_bytes < MIN_CHUNK_BYTES {
+        return Err("The chunk size is smaller than a row of P1 and P2");
+    }
+
+    // Derive pk_seed and Oil space from sk_seed
+    let mut s = [0u8; PK_SEED_BYTES + O_BYTES];
+    safe_shake256(
+        &mut s,
+        (PK_SEED_BYTES + O_BYTES) as u64,
+        &compact_secret_key,
+        SK_SEED_BYTES as u64,
+    );
+
+    let pk_seed = &s[0..PK_SEED_BYTES];
+    let o_bytes = &s[PK_SEED_BYTES..PK_SEED_BYTES + O_BYTES];
+    let o = decode_bytestring_matrix_array!(o_bytes, V, O);
+
+    // Buffer for the rows of P1 and P2 currently in memory
+    let chunk_u32s = (chunk_bytes / 4).min((P1_BYTES + P2_BYTES) / 4);
+    let mut chunk = vec![0u32; chunk_u32s];
+
+    Ok(sign_core(compact_secret_key, &o, m_digest, r, |v| {
+        // M_i = v_i * L and v^t * P1 * v
+        let mut m_matrices_array = [0u32; K * O * M / 8];
+        let mut vt_p1_v = [0u32; K * K * M / 8];
+
+        let mut row = 0;
+        let mut p1_offset = 0; // Offset of the chunk in P1 (in u32)
+        while row < V {
+            // Take as many whole rows of P1 and P2 as fit in the buffer
+            let first_row = row;
+            let mut p1_len = 0;
+            let mut chunk_len = 0;
+            while row < V && chunk_len + (V - row + O) * U32_PER_IDX <= chunk_u32s {
+                p1_len += (V - row) * U32_PER_IDX;
+                chunk_len += (V - row + O) * U32_PER_IDX;
+                row += 1;
+            }
+
+            // P2 directly follows P1 in the AES output
+            let (p1_rows, p2_rows) = chunk[..chunk_len].split_at_mut(p1_len);
+            safe_aes_128_ctr_offset(
+                p1_rows,
+                (p1_offset * 4) as u64,
+                (p1_len * 4) as u64,
+                pk_seed,
+            );
+            safe_aes_128_ctr_offset(
+                p2_rows,
+                (P1_BYTES + first_row * O * U32_PER_IDX * 4) as u64,
+                ((chunk_len - p1_len) * 4) as u64,
+                pk_seed,
+            );
+
+            let mut diagonal_idx = 0; // Index of entry (r, r) in p1_rows
+            for r in first_row..row {
+                // Row r of P1 * v^t and of P1 * O + P2, right of the diagonal of P1
+                let mut p1_vt_row = [0u32; K * M / 8];
+                let mut p1_o_p2_row = [0u32; O * M / 8];
+                let p2_row = (r - first_row) * O * U32_PER_IDX;
+                p1_o_p2_row.copy_from_slice(&p2_rows[p2_row..p2_row + O * U32_PER_IDX]);
+
+                for c in r + 1..V {
+                    let p1_idx = diagonal_idx + (c - r) * U32_PER_IDX;
+                    for (k, v_k) in v.iter().enumerate() {
+                        mul_add_bitsliced_m_vec(
+                            p1_rows,
+                            p1_idx,
+                            v_k[c],
+                            &mut p1_vt_row,
+                            k * U32_PER_IDX,
+                        );
+                    }
+                    for (j, o_cj) in o[c].iter().enumerate() {
+                        mul_add_bitsliced_m_vec(
+                            p1_rows,
+                            p1_idx,
+                            *o_cj,
+                            &mut p1_o_p2_row,
+                            j * U32_PER_IDX,
+                        );
+                    }
+                }
+
+                // (P1 + P1^t)*O has no diagonal contribution. Entry (r, c) of P1 adds
+                // v_i[r] * P1[r][c] * O[c] and v_i[c] * P1[r][c] * O[r] to M_i
+                for (k, v_k) in v.iter().enumerate() {
+                    for (j, o_rj) in o[r].iter().enumerate() {
+                        let m_idx = (k * O + j) * U32_PER_IDX;
+                        mul_add_bitsliced_m_vec(
+                            &p1_o_p2_row,
+                            j * U32_PER_IDX,
+                            v_k[r],
+                            &mut m_matrices_array,
+                            m_idx,
+                        );
+                        mul_add_bitsliced_m_vec(
+                            &p1_vt_row,
+                            k * U32_PER_IDX,
+                            *o_rj,
+                            &mut m_matrices_array,
+                            m_idx,
+                        );
+                    }
+                }
+
+                // Complete row r of P1 * v^t with the diagonal and add its contribution to v * (P1 * v^t)
+                for (k, v_k) in v.iter().enumerate() {
+                    mul_add_bitsliced_m_vec(
+                        p1_rows,
+                        diagonal_idx,
+                        v_k[r],
+                        &mut p1_vt_row,
+                        k * U32_PER_IDX,
+                    );
+                }
+                for (k1, v_k1) in v.iter().enumerate() {
+                    for k2 in 0..K {
+                        mul_add_bitsliced_m_vec(
+                            &p1_vt_row,
+                            k2 * U32_PER_IDX,
+                            v_k1[r],
+                            &mut vt_p1_v,
+                            (k1 * K + k2) * U32_PER_IDX,
+                        );
+                    }
+                }
+                diagonal_idx += (V - r) * U32_PER_IDX;
+            }
+            p1_offset += p1_len;
+        }
+
+        (m_matrices_array, vt_p1_v)
+    }))
+}
+
+// Signing steps shared by `sign` and `sign_low_memory`. `p1_and_l_terms` computes the bitsliced
+// M_i = v_i * L matrices and v^t * P1 * v for the vinegar vectors of the current attempt.
+fn sign_core<F>(
+    compact_secret_key: [u8; CSK_BYTES],
+    o: &[[u8; O]; V],
+    m_digest: &[u8; DIGEST_BYTES],
+    r: [u8; R_BYTES],
+    mut p1_and_l_terms: F,
+) -> [u8; SIG_BYTES]
+where
+    F: FnMut(&[[u8; V]; K]) -> ([u32; K * O * M / 8], [u32; K * K * M / 8]),
+{
+    let mut x = Vector::<{ K * O }>::zero(); // Initialize x to zero
+    let mut v = [[0u8; V]; K]; // Initialize v to zero
+
+    // Derive salt
     let mut salt_input = [0u8; DIGEST_BYTES + R_BYTES + SK_SEED_BYTES];
-    salt_input[..DIGEST_BYTES].copy_from_slice(&m_digest);
+    salt_input[..DIGEST_BYTES].copy_from_slice(m_digest);
     salt_input[DIGEST_BYTES..DIGEST_BYTES + R_BYTES].copy_from_slice(&r);
     salt_input[DIGEST_BYTES + R_BYTES..].copy_from_slice(&compact_secret_key);
 
@@ -208,7 +533,7 @@ pub fn sign(compact_secret_key: [u8; CSK_BYTES], message: &[u8]) -> [u8; SIG_BYT
 
     // Derive t
     let mut t_shake_input = [0u8; DIGEST_BYTES + SALT_BYTES];
-    t_shake_input[..DIGEST_BYTES].copy_from_slice(&m_digest);
+    t_shake_input[..DIGEST_BYTES].copy_from_slice(m_digest);
     t_shake_input[DIGEST_BYTES..].copy_from_slice(&salt);
 
     let mut t_output = [0u8; M / 2]; // Ceil (M * log_2(q) / 8)
@@ -219,7 +544,9 @@ pub fn sign(compact_secret_key: [u8; CSK_BYTES], message: &[u8]) -> [u8; SIG_BYT
         (DIGEST_BYTES + SALT_BYTES) as u64,
     );
 
-    let t = decode_bytestring_to_array!(&t_output, M);
+    let t = Vector::<M>::from_nibbles(&decode_bytestring_to_array!(&t_output, M));
+    trace_value!("salt", bytes salt);
+    trace_value!("t", elements t.to_nibbles());
 
     // Derive v_i and r
     let mut v_shake_input = [
@@ -234,6 +561,7 @@ pub fn sign(compact_secret_key: [u8; CSK_BYTES], message: &[u8]) -> [u8; SIG_BYT
     for ctr in 0..=255 {
         // Derive v_i and r
         v_shake_input[DIGEST_BYTES + SALT_BYTES + CSK_BYTES] = ctr;
+        trace_value!("ctr", bytes[ctr]);
 
         const CEIL: usize = K * O / 2; // Ceil (K*O * log_2(q) / 8) - Notice, all versions does not require ceil
         let mut v_bytestring = [0u8; K * V_BYTES + CEIL];
@@ -247,88 +575,37 @@ pub fn sign(compact_secret_key: [u8; CSK_BYTES], message: &[u8]) -> [u8; SIG_BYT
         // Derive v_i
         for i in 0..K {
             let v_bytestring_slice = &v_bytestring[i * V_BYTES..(i + 1) * V_BYTES];
-            v[i] = decode_bytestring_to_array!(v_bytestring_slice, V)
+            v[i] = decode_bytestring_to_array!(v_bytestring_slice, V);
+            trace_value!(format!("v_{}", i), elements v[i]);
         }
 
         // Derive r (Notice r is redefined and have nothing to do with previous r)
         let v_bytestring_remainder = &v_bytestring[K * V_BYTES..];
         let r = decode_bytestring_to_array!(v_bytestring_remainder, K * O); // Remainding part of v_bytestring.
 
-        // Build the linear system Ax = y
-        let mut a = [[0u8; K * O]; M + SHIFTS];
-        let mut y = [0u8; M + SHIFTS];
-        y[..M].copy_from_slice(&t);
-        let mut ell = 0;
-
-        // Build K matrices of size M x O
-        let mut m_matrices_array = [0u32; K * O * M / 8];
-        mat_mul_bitsliced_mat_add!(v, l, &mut m_matrices_array, K, V, O);
-
-        let mut m_matrices = [[[0u8; O]; M]; K];
-
-        for (i, m_matrix) in m_matrices.iter_mut().enumerate().take(K) {
-            for j in 0..O {
-                let curr_idx = (M / 8) * (i * O + j);
-                let encoded_m = &m_matrices_array[curr_idx..curr_idx + U32_PER_IDX];
-                let decoded_m_row = decode_bit_sliced_array!(encoded_m);
-
-                for (col, value) in decoded_m_row.iter().enumerate() {
-                    m_matrix[col][j] = *value;
-                }
-            }
-        }
-
-        // v^t * P1
-        let mut vt_p1 = [0u32; V * K * M / 8];
-        bitsliced_mat_mul_transposed_mat_add!(p1, v, &mut vt_p1, V, V, K, 0, true);
-
-        // v^t * P1 * v
-        let mut vt_p1_v = [0u32; K * K * M / 8];
-        mat_mul_bitsliced_mat_add!(v, vt_p1, &mut vt_p1_v, K, V, K);
-
-        const SIZE: usize = K * (K + 1) / 2; // Size of upper triangular part of matrix of size K x K
-        let mut upper_vt_p1_v = [0u32; SIZE * M / 8];
-        upper!(vt_p1_v, &mut upper_vt_p1_v, K, K);
-
+        // M_i = v_i * L and v^t * P1 * v
+        let (m_matrices_array, vt_p1_v) = p1_and_l_terms(&v);
+        #[cfg(feature = "trace")]
         for i in 0..K {
-            for j in (i..K).rev() {
-                // Calculate position of in upper triangular part of matrix
-                let pos = i * K + j - (i * (i + 1) / 2);
-                let encoded_u =
-                    &upper_vt_p1_v[pos * U32_PER_IDX..(pos * U32_PER_IDX) + U32_PER_IDX];
-
-                let u = decode_bit_sliced_array!(encoded_u);
-
-                // y = y - u * z^ell - Instead of subtracting with shifted u,
-                // we just sub (XOR) with shifted y for easier loop structre
-                for d in 0..M {
-                    y[d + ell] ^= u[d];
-                }
-
-                // Update A cols with + z^ell * Mj
-                for col in i * O..(i + 1) * O {
-                    for row in 0..M {
-                        a[row + ell][col] ^= m_matrices[j][row][col % O];
-                    }
-                }
-
-                if i != j {
-                    // Update A cols with + z^ell * Mi
-                    for col in j * O..(j + 1) * O {
-                        for row in 0..M {
-                            a[row + ell][col] ^= m_matrices[i][row][col % O];
-                        }
-                    }
-                }
-                ell += 1;
-            }
+            trace_value!(
+                format!("M_{}", i),
+                elements m_matrix(&m_matrices_array, i).to_nibbles().concat()
+            );
         }
 
-        let y = reduce_mod_f(y);
-        let a = reduce_a_mod_f(a);
+        // Build the linear system Ax = y
+        let system = linear_system(&m_matrices_array, &vt_p1_v, t);
+        #[cfg(feature = "trace")]
+        {
+            let rows = unpack_rows(&system).to_nibbles();
+            let a: Vec<u8> = rows.iter().flat_map(|row| row[..K * O].to_vec()).collect();
+            let y: Vec<u8> = rows.iter().map(|row| row[K * O]).collect();
+            trace_value!("A", elements a);
+            trace_value!("y", elements y);
+        }
 
         // Try to solve the linear system Ax = y
-        match sample_solution(a, y, r) {
+        match sample_solution_packed(system, Vector::from_nibbles(&r)) {
             Ok(s) => x = s, // If Ok
             Err(_) => {
                 continue; // If Err (no solution found), continue to the next iteration of the loop
@@ -336,21 +613,24 @@ pub fn sign(compact_secret_key: [u8; CSK_BYTES], message: &[u8]) -> [u8; SIG_BYT
         };
         break; // If Ok, break the loop
     } // ctr loop ends
+    trace_value!("x", elements x.to_nibbles());
 
     // Finish and output signature
     let mut signature = [0u8; K * N];
+    let o = Matrix::from_nibbles(o);
 
     for i in 0..K {
-        let x_idx: [u8; O] = x[i * O..(i + 1) * O]
-            .try_into()
-            .expect("Slice has incorrect length");
-        let ox: [u8; V] = matrix_vec_mul!(o, x_idx, V, O); // (n−o) × o * o × 1 = (n−o) × 1
-
-        vec_add!(v[i], ox, V);
+        let x_idx = Vector::<O>(
+            x.0[i * O..(i + 1) * O]
+                .try_into()
+                .expect("Slice has incorrect length"),
+        );
+        let s_i = Vector::from_nibbles(&v[i]) + o * x_idx; // (n−o) × o * o × 1 = (n−o) × 1
 
-        signature[i * N..(i + 1) * N - O].copy_from_slice(&v[i]);
-        signature[i * N + V..(i + 1) * N].copy_from_slice(&x_idx);
+        signature[i * N..(i + 1) * N - O].copy_from_slice(&s_i.to_nibbles());
+        signature[i * N + V..(i + 1) * N].copy_from_slice(&x_idx.to_nibbles());
     }
+    trace_value!("s", elements signature);
 
     let mut sig_con_salt = [0u8; SIG_BYTES];
     let signature_encoded = encode_to_bytestring_array!(signature, K * N, SIG_BYTES - SALT_BYTES); // SALT_BYTES is NOT included in the signature
@@ -361,36 +641,227 @@ pub fn sign(compact_secret_key: [u8; CSK_BYTES], message: &[u8]) -> [u8; SIG_BYT
     sig_con_salt
 }
 
+// The linear system Ax = y of MAYO Algorithm 8 from the bitsliced M_i = v_i * L and v^t * P1 * v.
+// The columns of A and y are elements of GF(16)[z]/f(z) summing terms times z^ell. They are
+// accumulated as `BitslicedElement`s by Horner's rule over ell and written straight into the
+// packed rows of (A | y) that `sample_solution_packed` eliminates on.
+pub fn linear_system(
+    m_matrices_array: &[u32; K * O * M / 8],
+    vt_p1_v: &[u32; K * K * M / 8],
+    t: Vector<M>,
+) -> PackedRows {
+    const SIZE: usize = K * (K + 1) / 2; // Size of upper triangular part of matrix of size K x K
+    let mut upper_vt_p1_v = [0u32; SIZE * M / 8];
+    upper!(vt_p1_v, &mut upper_vt_p1_v, K, K);
+
+    // The pairs (i, j) in the order of ell
+    let mut pairs = [(0, 0); SIZE];
+    let mut ell = 0;
+    for i in 0..K {
+        for j in (i..K).rev() {
+            pairs[ell] = (i, j);
+            ell += 1;
+        }
+    }
+
+    let mut a = [BitslicedElement::zero(); K * O];
+    let mut u_sum = BitslicedElement::zero(); // Sum of u * z^ell
+    for &(i, j) in pairs.iter().rev() {
+        for column in a.iter_mut() {
+            column.mul_by_z();
+        }
+        u_sum.mul_by_z();
+
+        // Calculate position of in upper triangular part of matrix
+        let pos = i * K + j - (i * (i + 1) / 2);
+        u_sum += BitslicedElement::from_words(
+            &upper_vt_p1_v[pos * U32_PER_IDX..(pos + 1) * U32_PER_IDX],
+        );
+
+        // Update A cols with Mj, and with Mi if i != j
+        for col in 0..O {
+            a[i * O + col] += m_column(m_matrices_array, j, col);
+            if i != j {
+                a[j * O + col] += m_column(m_matrices_array, i, col);
+            }
+        }
+    }
+
+    // y = t - sum of u * z^ell
+    u_sum += BitslicedElement::from(ExtensionElement::from(t));
+
+    let mut system = [[0u64; WORDS]; M];
+    for (j, column) in a.iter().chain([&u_sum]).enumerate() {
+        pack_bitsliced_column(&mut system, j, column);
+    }
+    system
+}
+
+// Writes the bitsliced element into column j of the packed rows, element i going to row i
+fn pack_bitsliced_column(rows: &mut PackedRows, j: usize, column: &BitslicedElement) {
+    const U32_PER_TERM: usize = M / 32;
+    for (i, row) in rows.iter_mut().enumerate() {
+        let mut element = 0u64;
+        for b in 0..4 {
+            element |= (((column.0[b * U32_PER_TERM + i / 32] >> (i % 32)) & 1) as u64) << b;
+        }
+        row[j / 16] |= element << (4 * (j % 16));
+    }
+}
+
+// The same linear system built on unpacked elements, multiplying every term by z^ell on its own.
+// Kept as the reference for `linear_system` in tests and benchmarks.
+#[cfg(any(test, feature = "bench"))]
+pub fn linear_system_unpacked(
+    m_matrices_array: &[u32; K * O * M / 8],
+    vt_p1_v: &[u32; K * K * M / 8],
+    t: Vector<M>,
+) -> (Matrix<M, { K * O }>, Vector<M>) {
+    let mut a = [ExtensionElement::zero(); K * O];
+    let mut y = ExtensionElement::from(t);
+    let mut ell = 0;
+
+    // Column j of M_i
+    let m_columns: [[ExtensionElement; O]; K] = std::array::from_fn(|i| {
+        let m_i = m_matrix(m_matrices_array, i);
+        std::array::from_fn(|j| ExtensionElement::from(m_i.column(j)))
+    });
+
+    const SIZE: usize = K * (K + 1) / 2; // Size of upper triangular part of matrix of size K x K
+    let mut upper_vt_p1_v = [0u32; SIZE * M / 8];
+    upper!(vt_p1_v, &mut upper_vt_p1_v, K, K);
+
+    for i in 0..K {
+        for j in (i..K).rev() {
+            // Calculate position of in upper triangular part of matrix
+            let pos = i * K + j - (i * (i + 1) / 2);
+            let encoded_u = &upper_vt_p1_v[pos * U32_PER_IDX..(pos * U32_PER_IDX) + U32_PER_IDX];
+
+            let u = ExtensionElement::from(BitslicedElement::from_words(encoded_u));
+
+            // y = y - u * z^ell
+            y -= u.mul_by_z_pow(ell);
+
+            // Update A cols with + z^ell * Mj
+            for col in 0..O {
+                a[i * O + col] += m_columns[j][col].mul_by_z_pow(ell);
+            }
+
+            if i != j {
+                // Update A cols with + z^ell * Mi
+                for col in 0..O {
+                    a[j * O + col] += m_columns[i][col].mul_by_z_pow(ell);
+                }
+            }
+            ell += 1;
+        }
+    }
+
+    (Matrix::from_columns(&a.map(|column| column.0)), y.0)
+}
+
+// Column j of the bitsliced M_i
+fn m_column(m_matrices_array: &[u32; K * O * M / 8], i: usize, j: usize) -> BitslicedElement {
+    let curr_idx = U32_PER_IDX * (i * O + j);
+    BitslicedElement::from_words(&m_matrices_array[curr_idx..curr_idx + U32_PER_IDX])
+}
+
+#[cfg(any(test, feature = "bench", feature = "trace"))]
+fn m_matrix(m_matrices_array: &[u32; K * O * M / 8], i: usize) -> Matrix<M, O> {
+    let columns: [Vector<M>; O] = std::array::from_fn(|j| {
+        ExtensionElement::from(m_column(m_matrices_array, i, j)).0
+    });
+    Matrix::from_columns(&columns)
+}
+
 // MAYO algorithm 9
 // Verify the signature of a message using the expanded public key
 pub fn verify(expanded_pk: ExpandedPublicKey, signature: &[u8], message: &[u8]) -> bool {
-    // Retrieve the public information from the expanded public key
-    let p1 = expanded_pk.p1;
-    let p2 = expanded_pk.p2;
-    let p3 = expanded_pk.p3;
+    verify_digest(expanded_pk, signature, &message_digest(message))
+}
+
+// Verify a signature given the digest of the message, e.g. as produced by a `MessageHasher`
+pub fn verify_digest(
+    expanded_pk: ExpandedPublicKey,
+    signature: &[u8],
+    m_digest: &[u8; DIGEST_BYTES],
+) -> bool {
+    verify_expanded_digest(&expanded_pk, signature, m_digest)
+}
+
+// Verifies a batch of (public key, message, signature) entries and returns whether each entry is valid.
+// Entries are grouped by public key so every distinct key is only expanded once.
+// With the `rayon` feature the keys and the signatures of each key are verified in parallel.
+pub fn verify_batch(entries: &[(&CompactPublicKey, &[u8], &[u8])]) -> Vec<bool> {
+    // Indices of the entries belonging to each distinct public key
+    let mut groups: HashMap<&CompactPublicKey, Vec<usize>> = HashMap::new();
+    for (i, (cpk, _, _)) in entries.iter().enumerate() {
+        groups.entry(*cpk).or_default().push(i);
+    }
+    let groups: Vec<(&CompactPublicKey, Vec<usize>)> = groups.into_iter().collect();
+
+    let verify_group = |(cpk, indices): &(&CompactPublicKey, Vec<usize>)| {
+        let expanded_pk = expand_pk((*cpk).clone());
+
+        let verify_entry = |&i: &usize| {
+            let (_, message, signature) = entries[i];
+            let valid = verify_expanded_digest(&expanded_pk, signature, &message_digest(message));
+            (i, valid)
+        };
+
+        #[cfg(feature = "rayon")]
+        {
+            use rayon::prelude::*;
+            indices.par_iter().map(verify_entry).collect::<Vec<_>>()
+        }
+
+        #[cfg(not(feature = "rayon"))]
+        {
+            indices.iter().map(verify_entry).collect::<Vec<_>>()
+        }
+    };
+
+    #[cfg(feature = "rayon")]
+    let group_results: Vec<Vec<(usize, bool)>> = {
+        use rayon::prelude::*;
+        groups.par_iter().map(verify_group).collect()
+    };
+
+    #[cfg(not(feature = "rayon"))]
+    let group_results: Vec<Vec<(usize, bool)>> = groups.iter().map(verify_group).collect();
+
+    let mut results = vec![false; entries.len()];
+    for (i, valid) in group_results.into_iter().flatten() {
+        results[i] = valid;
+    }
+    results
+}
+
+// Verifies a signature on a message digest without taking ownership of the expanded public key
+fn verify_expanded_digest(
+    expanded_pk: &ExpandedPublicKey,
+    signature: &[u8],
+    m_digest: &[u8; DIGEST_BYTES],
+) -> bool {
+    // Signatures come from untrusted input, a signature with missing or trailing bytes is invalid
+    if signature.len() != SIG_BYTES {
+        return false;
+    }
 
     // Decode signature and derive salt
     let salt = &signature[SIG_BYTES - SALT_BYTES..SIG_BYTES];
     let s_bytes = &signature[0..SIG_BYTES - SALT_BYTES];
     let s = decode_bytestring_to_array!(s_bytes, K * N);
+    trace_value!("s", elements s);
 
     let mut s_matrix = [[0u8; N]; K];
     for i in 0..K {
         s_matrix[i].copy_from_slice(&s[i * N..(i + 1) * N]);
     }
 
-    // Hash message
-    let mut m_digest = [0u8; DIGEST_BYTES];
-    safe_shake256(
-        &mut m_digest,
-        DIGEST_BYTES as u64,
-        message,
-        message.len() as u64,
-    );
-
     // Derive t
     let mut t_shake_input = [0u8; DIGEST_BYTES + SALT_BYTES];
-    t_shake_input[..DIGEST_BYTES].copy_from_slice(&m_digest);
+    t_shake_input[..DIGEST_BYTES].copy_from_slice(m_digest);
     t_shake_input[DIGEST_BYTES..].copy_from_slice(salt);
 
     let mut t_output = [0u8; M / 2]; // Ceil (M * log_2(q) / 8)
@@ -400,14 +871,11 @@ pub fn verify(expanded_pk: ExpandedPublicKey, signature: &[u8], message: &[u8])
         &t_shake_input,
         (DIGEST_BYTES + SALT_BYTES) as u64,
     );
-    let t = decode_bytestring_to_array!(t_output, M);
-
-    // Compute P*(s)
-    let mut y = [0u8; M + SHIFTS];
-    let mut ell = 0;
+    let t = Vector::<M>::from_nibbles(&decode_bytestring_to_array!(t_output, M));
+    trace_value!("t", elements t.to_nibbles());
 
     // Compute s^t * P
-    let st_p = calculate_st_p(p1, p2, p3, s_matrix);
+    let st_p = calculate_st_p(&expanded_pk.p1, &expanded_pk.p2, &expanded_pk.p3, s_matrix);
 
     // Compute s^t * P * s
     let mut st_p_s = [0u32; K * K * M / 8];
@@ -417,29 +885,54 @@ pub fn verify(expanded_pk: ExpandedPublicKey, signature: &[u8], message: &[u8])
     let mut upper_st_p_s = [0u32; SIZE * M / 8];
     upper!(&st_p_s, &mut upper_st_p_s, K, K);
 
-    for i in 0..K {
-        for j in (i..K).rev() {
-            // // Calculate position of in upper triangular part of matrix
-            let pos = i * K + j - (i * (i + 1) / 2);
-            let encoded_u = &upper_st_p_s[pos * U32_PER_IDX..(pos * U32_PER_IDX) + U32_PER_IDX];
-
-            let u = decode_bit_sliced_array!(encoded_u);
+    // Compute P*(s) = - sum of u * z^ell, accumulated as a `BitslicedElement` by Horner's rule
+    // over ell as in `linear_system`, going through the pairs (i, j) from the last ell to the first
+    let mut u_sum = BitslicedElement::zero();
+    for i in (0..K).rev() {
+        for j in i..K {
+            u_sum.mul_by_z();
 
-            // y = y - u * z^ell - Instead of subtracting with shifted u,
-            // sub (XOR) with shifted y.
-            for d in 0..M {
-                y[d + ell] ^= u[d];
-            }
-            ell += 1;
+            // Calculate position of in upper triangular part of matrix
+            let pos = i * K + j - (i * (i + 1) / 2);
+            u_sum += BitslicedElement::from_words(
+                &upper_st_p_s[pos * U32_PER_IDX..(pos + 1) * U32_PER_IDX],
+            );
         }
     }
 
-    let y = reduce_mod_f(y);
+    let y = Vector::<M>::zero() - ExtensionElement::from(u_sum).0;
+    trace_value!("y", elements y.to_nibbles());
 
     // Accept signature if y = t
     y == t
 }
 
+// Signs a message bound to a usage context (like the ctx parameter of ML-DSA).
+// The signature only verifies with `verify_with_context` under the same context.
+// With an empty context this is the same as `sign`.
+pub fn sign_with_context(
+    compact_secret_key: [u8; CSK_BYTES],
+    message: &[u8],
+    context: &[u8],
+) -> Result<[u8; SIG_BYTES], &'static str> {
+    let m_digest = context_message_digest(message, context)?;
+
+    Ok(sign_digest(compact_secret_key, &m_digest))
+}
+
+// Verify a signature made with `sign_with_context`. Signatures with an empty context are plain MAYO signatures.
+pub fn verify_with_context(
+    expanded_pk: ExpandedPublicKey,
+    signature: &[u8],
+    message: &[u8],
+    context: &[u8],
+) -> bool {
+    match context_message_digest(message, context) {
+        Ok(m_digest) => verify_digest(expanded_pk, signature, &m_digest),
+        Err(_) => false, // No valid signature exists for an invalid context
+    }
+}
+
 //API algorithms
 
 // MAYO algorithm 10
@@ -460,6 +953,11 @@ pub fn api_sign(message: Vec<u8>, csk: [u8; CSK_BYTES]) -> Vec<u8> {
 // MAYO algorithm 11
 // Expand a public key from its compact representation and verify a signature
 pub fn api_sign_open(sign_con_mes: Vec<u8>, cpk: CompactPublicKey) -> (bool, Vec<u8>) {
+    // A signed message shorter than a signature is invalid
+    if sign_con_mes.len() < SIG_BYTES {
+        return (false, vec![0u8]);
+    }
+
     // Expand public key
     let expanded_pk = expand_pk(cpk);
 
@@ -476,46 +974,34 @@ pub fn api_sign_open(sign_con_mes: Vec<u8>, cpk: CompactPublicKey) -> (bool, Vec
     (result, message_slice.to_vec()) // Only convert if valid signature
 }
 
-// Perform reduction of a polynomial with f(z)
-pub fn reduce_mod_f(mut polynomial: [u8; M + SHIFTS]) -> [u8; M] {
-    for i in (M..polynomial.len()).rev() {
-        for (shift, coef) in F_Z.iter() {
-            let mul_res = mul(polynomial[i], *coef);
-            polynomial[i - M + *shift] ^= mul_res // Same as add
-        }
-        polynomial[i] = 0; // set original term to 0 After distributing coefficient
-    }
-
-    let mut reduced_polynomial = [0u8; M];
-    reduced_polynomial.copy_from_slice(&polynomial[..M]); // Truncate the polynomial to M terms (all other entries are zero after reduction)
-
-    reduced_polynomial
+// Hash a message to its digest (first step of both sign and verify)
+pub(crate) fn message_digest(message: &[u8]) -> [u8; DIGEST_BYTES] {
+    let mut m_digest = [0u8; DIGEST_BYTES];
+    safe_shake256(
+        &mut m_digest,
+        DIGEST_BYTES as u64,
+        message,
+        message.len() as u64,
+    );
+    m_digest
 }
 
-// Perform reduction of a matrix's cols with f(z)
-pub fn reduce_a_mod_f(mut a: [[u8; K * O]; M + SHIFTS]) -> [[u8; K * O]; M] {
-    for col in 0..K * O {
-        for row in (M..M + SHIFTS).rev() {
-            for (shift, coef) in F_Z.iter() {
-                let mul_res = mul(a[row][col], *coef);
-
-                a[row - M + *shift][col] ^= mul_res; // Same as add
-            }
-            a[row][col] = 0; // set original term to 0 After distributing coefficient
-        }
-    }
-
-    let mut reduced_a = [[0u8; K * O]; M];
-    for i in 0..M {
-        reduced_a[i].copy_from_slice(&a[i]); // Truncate the polynomial to M terms (all other rows are zero after reduction)
-    }
+// Hash a message together with its length-prefixed context (see `MessageHasher::with_context`)
+fn context_message_digest(
+    message: &[u8],
+    context: &[u8],
+) -> Result<[u8; DIGEST_BYTES], &'static str> {
+    let mut hasher = MessageHasher::with_context(context)?;
+    hasher.update(message);
 
-    reduced_a
+    Ok(hasher.finalize())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::sample::{unpack_rows, COLS};
+    use crate::utils::random_words;
 
     #[test]
     fn test_main_functionality_mayo_1() {
@@ -570,4 +1056,195 @@ mod tests {
             assert_eq!(verif, true);
         }
     }
+
+    // Expands the secret key in its own stack frame
+    fn sign_batch_compact(csk: [u8; CSK_BYTES], messages: &[&[u8]]) -> Vec<[u8; SIG_BYTES]> {
+        sign_batch(&expand_sk(csk), messages)
+    }
+
+    #[test]
+    fn test_sign_batch() {
+        let (cpk, csk) = compact_key_gen();
+        let messages: Vec<Vec<u8>> = (0..5u8).map(|i| vec![i; 32 * i as usize]).collect();
+        let message_refs: Vec<&[u8]> = messages.iter().map(|m| m.as_slice()).collect();
+
+        let signatures = sign_batch_compact(csk, &message_refs);
+        assert_eq!(signatures.len(), messages.len());
+
+        for (signature, message) in signatures.iter().zip(messages.iter()) {
+            let mut sign_con_mes = signature.to_vec();
+            sign_con_mes.extend_from_slice(message);
+            let (verif, opened) = api_sign_open(sign_con_mes, cpk.clone());
+            assert!(verif);
+            assert_eq!(&opened, message);
+        }
+
+        assert!(sign_batch_compact(csk, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        let (cpk_1, csk_1) = compact_key_gen();
+        let (cpk_2, csk_2) = compact_key_gen();
+        let message_1 = b"first message".as_slice();
+        let message_2 = b"second message".as_slice();
+
+        let signature_1 = sign(csk_1, message_1);
+        let signature_2 = sign(csk_2, message_2);
+        let mut tampered = signature_1;
+        tampered[0] ^= 1;
+
+        let entries: [(&CompactPublicKey, &[u8], &[u8]); 7] = [
+            (&cpk_1, message_1, &signature_1),      // valid
+            (&cpk_2, message_2, &signature_2),      // valid
+            (&cpk_1, message_2, &signature_1),      // wrong message
+            (&cpk_2, message_1, &signature_1),      // wrong key
+            (&cpk_1, message_1, &tampered),         // invalid signature
+            (&cpk_1, message_1, &signature_1[1..]), // truncated signature
+            (&cpk_2, message_2, &signature_2),      // valid (duplicate)
+        ];
+
+        assert_eq!(
+            verify_batch(&entries),
+            vec![true, true, false, false, false, false, true]
+        );
+        assert!(verify_batch(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_malformed_signature_lengths() {
+        let (cpk, csk) = compact_key_gen();
+        let message = b"message".as_slice();
+        let signature = sign(csk, message);
+
+        let verify_bytes = |signature: &[u8]| verify(expand_pk(cpk.clone()), signature, message);
+        assert!(verify_bytes(&signature));
+        assert!(!verify_bytes(&signature[..SIG_BYTES - 1]));
+        assert!(!verify_bytes(&[signature.as_slice(), &[0]].concat()));
+        assert!(!verify_bytes(&[]));
+
+        assert_eq!(
+            api_sign_open(signature[..10].to_vec(), cpk.clone()),
+            (false, vec![0u8])
+        );
+        let signed_message = [signature.as_slice(), message].concat();
+        assert_eq!(api_sign_open(signed_message, cpk), (true, message.to_vec()));
+    }
+
+    #[test]
+    fn test_sign_low_memory_matches_sign() {
+        let (cpk, csk) = compact_key_gen();
+        let mut message = [0u8; 32];
+        safe_random_bytes(&mut message, 32);
+        let r = [7u8; R_BYTES];
+
+        let expected = sign_digest_with_randomness(csk, &message_digest(&message), r);
+
+        // One row at a time, a few rows at a time and all of P1 and P2 at once
+        for chunk_bytes in [MIN_CHUNK_BYTES, 3 * MIN_CHUNK_BYTES, usize::MAX] {
+            let signature =
+                sign_low_memory_with_randomness(csk, &message_digest(&message), chunk_bytes, r);
+            assert_eq!(signature, Ok(expected));
+        }
+        assert!(sign_low_memory_with_randomness(
+            csk,
+            &message_digest(&message),
+            MIN_CHUNK_BYTES - 1,
+            r
+        )
+        .is_err());
+
+        let mut sign_con_mes = expected.to_vec();
+        sign_con_mes.extend_from_slice(&message);
+        let (verif, _) = api_sign_open(sign_con_mes, cpk);
+        assert!(verif);
+    }
+
+    #[test]
+    fn test_empty_context_is_plain_mayo() {
+        let mut message = [0u8; 32];
+        safe_random_bytes(&mut message, 32);
+
+        assert_eq!(
+            context_message_digest(&message, &[]).unwrap(),
+            message_digest(&message)
+        );
+    }
+
+    #[test]
+    fn test_sign_with_context() {
+        let (cpk, csk) = compact_key_gen();
+        let mut message = [0u8; 32];
+        safe_random_bytes(&mut message, 32);
+
+        let signature = sign_with_context(csk, &message, b"firmware-manifest").unwrap();
+
+        assert!(verify_context(
+            &cpk,
+            &signature,
+            &message,
+            b"firmware-manifest"
+        ));
+        assert!(!verify_context(&cpk, &signature, &message, b"api-token"));
+        assert!(!verify_context(&cpk, &signature, &message, &[]));
+    }
+
+    #[test]
+    fn test_context_is_separated_from_plain_messages() {
+        let (cpk, csk) = compact_key_gen();
+        let context = b"firmware-manifest";
+        let message = b"message";
+        let prefixed = [&[context.len() as u8][..], context, message].concat();
+
+        // A plain signature of [len] || ctx || m is not a signature of m under ctx
+        let plain_signature = sign(csk, &prefixed);
+        assert!(!verify_context(&cpk, &plain_signature, message, context));
+
+        // and a signature of m under ctx is not a plain signature of [len] || ctx || m
+        let context_signature = sign_with_context(csk, message, context).unwrap();
+        let mut sign_con_mes = context_signature.to_vec();
+        sign_con_mes.extend_from_slice(&prefixed);
+        let (verif, _) = api_sign_open(sign_con_mes, cpk);
+        assert!(!verif);
+    }
+
+    // Keeps the expanded public key out of the calling test's stack frame
+    fn verify_context(
+        cpk: &CompactPublicKey,
+        signature: &[u8],
+        message: &[u8],
+        context: &[u8],
+    ) -> bool {
+        verify_with_context(expand_pk(cpk.clone()), signature, message, context)
+    }
+
+    #[test]
+    fn test_context_too_long() {
+        let (_, csk) = compact_key_gen();
+        let context = [0u8; 256];
+
+        assert!(sign_with_context(csk, b"message", &context).is_err());
+        assert!(sign_with_context(csk, b"message", &context[..255]).is_ok());
+    }
+
+    #[test]
+    fn test_linear_system_matches_unpacked() {
+        for _ in 0..4 {
+            let m_matrices_array = random_words::<{ K * O * M / 8 }>();
+            let vt_p1_v = random_words::<{ K * K * M / 8 }>();
+            let t = Vector::from_nibbles(&random_words::<M>().map(|w| (w & 0xf) as u8));
+
+            let (a, y) = linear_system_unpacked(&m_matrices_array, &vt_p1_v, t);
+            let mut expected = Matrix::<M, COLS>::zero();
+            for i in 0..M {
+                expected[i][..K * O].copy_from_slice(&a[i]);
+                expected[i][K * O] = y[i];
+            }
+
+            assert_eq!(
+                unpack_rows(&linear_system(&m_matrices_array, &vt_p1_v, t)),
+                expected
+            );
+        }
+    }
 }
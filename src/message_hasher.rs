@@ -0,0 +1,198 @@
+use std::io::{self, Read, Write};
+
+use crate::constants::DIGEST_BYTES;
+use crate::crypto_primitives::{
+    safe_cshake256_inc_finalize, safe_cshake256_inc_init, safe_shake256_inc_absorb,
+    safe_shake256_inc_ctx_release, safe_shake256_inc_finalize, safe_shake256_inc_init,
+    safe_shake256_inc_squeeze, Shake256State,
+};
+
+// Longest context string that fits the one byte length prefix
+pub const MAX_CONTEXT_BYTES: usize = 255;
+
+// cSHAKE256 customization string of digests bound to a non-empty context
+const CONTEXT_CUSTOMIZATION: &[u8] = b"MAYO context";
+
+// Incremental message digest for sign and verify, so a message does not need to be in memory at once.
+// Feeding a message in any number of chunks gives the same digest as hashing it in one go,
+// and signing the digest with `sign_digest` gives the same signature as `sign`.
+pub struct MessageHasher {
+    state: Shake256State,
+    customized: bool,
+}
+
+impl MessageHasher {
+    pub fn new() -> Self {
+        MessageHasher {
+            state: safe_shake256_inc_init(),
+            customized: false,
+        }
+    }
+
+    // Hasher that binds the digest to a usage context, as done by `sign_with_context`.
+    // A non-empty context (at most 255 bytes) is absorbed with a one byte length prefix before the
+    // message into cSHAKE256 with its own customization string instead of SHAKE256, so no plain
+    // message has the digest of a message under a context, or the other way around.
+    // An empty context gives the plain MAYO message digest.
+    pub fn with_context(context: &[u8]) -> Result<Self, &'static str> {
+        if context.len() > MAX_CONTEXT_BYTES {
+            return Err("The context string is longer than 255 bytes");
+        }
+        if context.is_empty() {
+            return Ok(Self::new());
+        }
+
+        let mut hasher = MessageHasher {
+            state: safe_cshake256_inc_init(CONTEXT_CUSTOMIZATION),
+            customized: true,
+        };
+        hasher.update(&[context.len() as u8]);
+        hasher.update(context);
+        Ok(hasher)
+    }
+
+    // Absorb the next chunk of the message
+    pub fn update(&mut self, chunk: &[u8]) {
+        safe_shake256_inc_absorb(&mut self.state, chunk);
+    }
+
+    // Absorb everything left in `reader`. Returns the number of bytes read.
+    pub fn update_from_reader<R: Read>(&mut self, reader: &mut R) -> io::Result<u64> {
+        io::copy(reader, self)
+    }
+
+    // Finish absorbing and return the message digest used by sign and verify
+    pub fn finalize(mut self) -> [u8; DIGEST_BYTES] {
+        let mut m_digest = [0u8; DIGEST_BYTES];
+        if self.customized {
+            safe_cshake256_inc_finalize(&mut self.state);
+        } else {
+            safe_shake256_inc_finalize(&mut self.state);
+        }
+        safe_shake256_inc_squeeze(&mut m_digest, &mut self.state);
+        m_digest
+    }
+}
+
+impl Default for MessageHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Write for MessageHasher {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for MessageHasher {
+    fn drop(&mut self) {
+        safe_shake256_inc_ctx_release(&mut self.state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto_primitives::{safe_random_bytes, safe_shake256};
+    use crate::mayo_functionality::{
+        api_sign_open, compact_key_gen, expand_pk, sign, sign_digest, verify_digest,
+        CompactPublicKey,
+    };
+
+    #[test]
+    fn test_chunked_digest_matches_one_shot() {
+        let mut message = vec![0u8; 1000];
+        safe_random_bytes(&mut message, 1000);
+
+        let mut expected = [0u8; DIGEST_BYTES];
+        safe_shake256(
+            &mut expected,
+            DIGEST_BYTES as u64,
+            &message,
+            message.len() as u64,
+        );
+
+        // Chunk sizes below, at and above the SHAKE256 rate of 136 bytes
+        for chunk_size in [1, 7, 136, 137, 1000] {
+            let mut hasher = MessageHasher::new();
+            for chunk in message.chunks(chunk_size) {
+                hasher.update(chunk);
+            }
+            assert_eq!(hasher.finalize(), expected);
+        }
+
+        let mut hasher = MessageHasher::new();
+        let read = hasher.update_from_reader(&mut message.as_slice()).unwrap();
+        assert_eq!(read, 1000);
+        assert_eq!(hasher.finalize(), expected);
+    }
+
+    #[test]
+    fn test_sign_streamed_message() {
+        let (cpk, csk) = compact_key_gen();
+        let mut message = vec![0u8; 5000];
+        safe_random_bytes(&mut message, 5000);
+
+        let mut hasher = MessageHasher::new();
+        hasher.update_from_reader(&mut message.as_slice()).unwrap();
+        let signature = sign_digest(csk, &hasher.finalize());
+
+        // Streamed signature verifies with the one-shot API
+        let mut sign_con_mes = signature.to_vec();
+        sign_con_mes.extend_from_slice(&message);
+        let (verif, _) = api_sign_open(sign_con_mes, cpk);
+        assert!(verif);
+    }
+
+    #[test]
+    fn test_verify_streamed_message() {
+        let (cpk, csk) = compact_key_gen();
+        let mut message = vec![0u8; 5000];
+        safe_random_bytes(&mut message, 5000);
+        let signature = sign(csk, &message);
+
+        let mut hasher = MessageHasher::new();
+        for chunk in message.chunks(1024) {
+            hasher.update(chunk);
+        }
+        let m_digest = hasher.finalize();
+
+        assert!(verify_expanded(cpk, &signature, &m_digest));
+    }
+
+    // Sample #3 of the NIST cSHAKE examples: cSHAKE256 of 00 01 02 03 with an empty function name
+    // and the customization string "Email Signature"
+    #[test]
+    fn test_cshake256_sample() {
+        let mut state = safe_cshake256_inc_init(b"Email Signature");
+        safe_shake256_inc_absorb(&mut state, &[0x00, 0x01]);
+        safe_shake256_inc_absorb(&mut state, &[0x02, 0x03]);
+        safe_cshake256_inc_finalize(&mut state);
+        let mut output = [0u8; 64];
+        safe_shake256_inc_squeeze(&mut output, &mut state);
+        safe_shake256_inc_ctx_release(&mut state);
+
+        let expected = "D008828E2B80AC9D2218FFEE1D070C48B8E4C87BFF32C9699D5B6896EEE0EDD1\
+                        64020E2BE0560858D9C00C037E34A96937C561A74C412BB4C746469527281C8C";
+        let expected: Vec<u8> = (0..64)
+            .map(|i| u8::from_str_radix(&expected[2 * i..2 * i + 2], 16).unwrap())
+            .collect();
+        assert_eq!(output.to_vec(), expected);
+    }
+
+    // Keeps the expanded public key out of the test's stack frame while signing
+    fn verify_expanded(
+        cpk: CompactPublicKey,
+        signature: &[u8],
+        m_digest: &[u8; DIGEST_BYTES],
+    ) -> bool {
+        verify_digest(expand_pk(cpk), signature, m_digest)
+    }
+}
@@ -0,0 +1,289 @@
+// Rust implementations of the C primitives in src/genkat and src/arm_neon_intrinsic, with the same
+// signatures as their wrappers in crypto_primitives. They are used for targets the C sources can
+// not be built for (wasm32) and with the `portable` feature. The output is identical to the C
+// code, so keys, signatures and KAT files do not depend on the backend.
+use std::sync::Mutex;
+
+use aes::cipher::{BlockEncrypt, KeyInit, KeyIvInit, StreamCipher, StreamCipherSeek};
+use aes::{Aes128, Aes256};
+use sha3::digest::{ExtendableOutput, Update, XofReader};
+use sha3::{CShake256, CShake256Core, CShake256Reader, Shake256, Shake256Reader};
+
+// AES-128 in counter mode with a zero IV and a 32-bit big-endian block counter, as in aes_c.c
+type Aes128Ctr = ctr::Ctr32BE<Aes128>;
+
+const SEED_BYTES: usize = 48;
+
+/* RANDOMNESS: NIST AES-256 CTR-DRBG (randombytes_ctrdrbg.c) */
+
+struct CtrDrbg {
+    key: [u8; 32],
+    v: [u8; 16],
+}
+
+// Global state like the C DRBG, but behind a mutex
+static DRBG: Mutex<CtrDrbg> = Mutex::new(CtrDrbg {
+    key: [0u8; 32],
+    v: [0u8; 16],
+});
+
+impl CtrDrbg {
+    fn increment_v(&mut self) {
+        for byte in self.v.iter_mut().rev() {
+            let (value, overflow) = byte.overflowing_add(1);
+            *byte = value;
+            if !overflow {
+                break;
+            }
+        }
+    }
+
+    // Next block of the AES-256 keystream
+    fn next_block(&mut self) -> [u8; 16] {
+        self.increment_v();
+        let mut block = self.v.into();
+        Aes256::new(&self.key.into()).encrypt_block(&mut block);
+        block.into()
+    }
+
+    fn update(&mut self, provided_data: Option<&[u8; SEED_BYTES]>) {
+        let mut temp = [0u8; SEED_BYTES];
+        for chunk in temp.chunks_mut(16) {
+            chunk.copy_from_slice(&self.next_block());
+        }
+        if let Some(provided_data) = provided_data {
+            for (t, p) in temp.iter_mut().zip(provided_data) {
+                *t ^= p;
+            }
+        }
+        self.key.copy_from_slice(&temp[..32]);
+        self.v.copy_from_slice(&temp[32..]);
+    }
+}
+
+pub fn safe_random_bytes_init(
+    entropy_input: &mut [u8],
+    personalization_string: &[u8],
+    _security_strength: i32,
+) {
+    let mut seed_material = [0u8; SEED_BYTES];
+    seed_material.copy_from_slice(&entropy_input[..SEED_BYTES]);
+    for (s, p) in seed_material.iter_mut().zip(personalization_string) {
+        *s ^= p;
+    }
+
+    let mut drbg = DRBG.lock().expect("The randomness source is poisoned");
+    drbg.key = [0u8; 32];
+    drbg.v = [0u8; 16];
+    drbg.update(Some(&seed_material));
+}
+
+pub fn safe_random_bytes(random_arrays: &mut [u8], nbytes: u64) {
+    let mut drbg = DRBG.lock().expect("The randomness source is poisoned");
+    for chunk in random_arrays[..nbytes as usize].chunks_mut(16) {
+        let block = drbg.next_block();
+        chunk.copy_from_slice(&block[..chunk.len()]);
+    }
+    drbg.update(None);
+}
+
+/* AES-128-CTR */
+
+pub fn safe_aes_128_ctr(output: &mut [u32], output_byte_len: u64, input: &[u8]) {
+    safe_aes_128_ctr_offset(output, 0, output_byte_len, input);
+}
+
+// AES-128-CTR keystream starting `output_byte_offset` bytes into the stream (must be a multiple of the 16 byte block size)
+pub fn safe_aes_128_ctr_offset(
+    output: &mut [u32],
+    output_byte_offset: u64,
+    output_byte_len: u64,
+    input: &[u8],
+) {
+    debug_assert!(output_byte_offset.is_multiple_of(16));
+    let mut cipher = Aes128Ctr::new(input[..16].into(), &[0u8; 16].into());
+    cipher.seek(output_byte_offset);
+
+    let mut keystream = vec![0u8; output_byte_len as usize];
+    cipher.apply_keystream(&mut keystream);
+
+    // The C code writes the keystream bytes into the words in memory order (little-endian)
+    for (word, bytes) in output.iter_mut().zip(keystream.chunks(4)) {
+        let mut word_bytes = word.to_le_bytes();
+        word_bytes[..bytes.len()].copy_from_slice(bytes);
+        *word = u32::from_le_bytes(word_bytes);
+    }
+}
+
+/* SHAKE256 */
+
+pub fn safe_shake256(output: &mut [u8], output_byte_len: u64, input: &[u8], input_byte_len: u64) {
+    let mut hasher = Shake256::default();
+    hasher.update(&input[..input_byte_len as usize]);
+    hasher
+        .finalize_xof()
+        .read(&mut output[..output_byte_len as usize]);
+}
+
+// State of the incremental SHAKE256 and cSHAKE256 API, replaces shake256incctx of fips202.c
+pub enum Shake256State {
+    Absorbing(Shake256),
+    Squeezing(Shake256Reader),
+    CustomAbsorbing(CShake256),
+    CustomSqueezing(CShake256Reader),
+    Released,
+}
+
+pub fn safe_shake256_inc_init() -> Shake256State {
+    Shake256State::Absorbing(Shake256::default())
+}
+
+pub fn safe_shake256_inc_absorb(state: &mut Shake256State, input: &[u8]) {
+    match state {
+        Shake256State::Absorbing(hasher) => hasher.update(input),
+        Shake256State::CustomAbsorbing(hasher) => hasher.update(input),
+        _ => panic!("SHAKE256 absorb after finalize"),
+    }
+}
+
+pub fn safe_shake256_inc_finalize(state: &mut Shake256State) {
+    *state = match std::mem::replace(state, Shake256State::Released) {
+        Shake256State::Absorbing(hasher) => Shake256State::Squeezing(hasher.finalize_xof()),
+        _ => panic!("SHAKE256 finalized twice"),
+    };
+}
+
+pub fn safe_shake256_inc_squeeze(output: &mut [u8], state: &mut Shake256State) {
+    match state {
+        Shake256State::Squeezing(reader) => reader.read(output),
+        Shake256State::CustomSqueezing(reader) => reader.read(output),
+        _ => panic!("SHAKE256 squeeze before finalize"),
+    }
+}
+
+pub fn safe_shake256_inc_ctx_release(state: &mut Shake256State) {
+    *state = Shake256State::Released;
+}
+
+pub fn safe_cshake256_inc_init(customization: &[u8]) -> Shake256State {
+    Shake256State::CustomAbsorbing(CShake256::from_core(CShake256Core::new(customization)))
+}
+
+pub fn safe_cshake256_inc_finalize(state: &mut Shake256State) {
+    *state = match std::mem::replace(state, Shake256State::Released) {
+        Shake256State::CustomAbsorbing(hasher) => {
+            Shake256State::CustomSqueezing(hasher.finalize_xof())
+        }
+        _ => panic!("cSHAKE256 finalized twice"),
+    };
+}
+
+/* BITSLICED MULTIPLY-ADD, replaces the NEON kernels in armv8_intrinsic.c */
+
+// acc += nibble * input for one bitsliced m-vector of `w` words per bit plane
+fn mul_add_bitsliced_m_vec(input: &[u32], nibble: u8, acc: &mut [u32], w: usize) {
+    let mask = |bit: u8| 0u32.wrapping_sub(((nibble >> bit) & 1) as u32);
+    let (n0, n1, n2, n3) = (mask(0), mask(1), mask(2), mask(3));
+
+    for i in 0..w {
+        let in0 = input[i];
+        let in1 = input[w + i];
+        let in2 = input[2 * w + i];
+        let in3 = input[3 * w + i];
+
+        // Multiplication by x reduced modulo x^4 + x + 1
+        let a = in0 ^ in3;
+        let b = in3 ^ in2;
+        let c = in2 ^ in1;
+
+        acc[i] ^= (n0 & in0) ^ (n1 & in3) ^ (n2 & in2) ^ (n3 & in1);
+        acc[w + i] ^= (n0 & in1) ^ (n1 & a) ^ (n2 & b) ^ (n3 & c);
+        acc[2 * w + i] ^= (n0 & in2) ^ (n1 & in1) ^ (n2 & a) ^ (n3 & b);
+        acc[3 * w + i] ^= (n0 & in3) ^ (n1 & in2) ^ (n2 & in1) ^ (n3 & a);
+    }
+}
+
+pub fn safe_mul_add_bitsliced_m_vec_mayo12(
+    input: &[u32],
+    input_start: i32,
+    nibble: u8,
+    acc: &mut [u32],
+    acc_start: i32,
+) {
+    mul_add_bitsliced_m_vec(
+        &input[input_start as usize..],
+        nibble,
+        &mut acc[acc_start as usize..],
+        2,
+    );
+}
+
+pub fn safe_mul_add_bitsliced_m_vec_mayo3(
+    input: &[u32],
+    input_start: i32,
+    nibble: u8,
+    acc: &mut [u32],
+    acc_start: i32,
+) {
+    mul_add_bitsliced_m_vec(
+        &input[input_start as usize..],
+        nibble,
+        &mut acc[acc_start as usize..],
+        3,
+    );
+}
+
+pub fn safe_mul_add_bitsliced_m_vec_mayo5(
+    input: &[u32],
+    input_start: i32,
+    nibble: u8,
+    acc: &mut [u32],
+    acc_start: i32,
+) {
+    mul_add_bitsliced_m_vec(
+        &input[input_start as usize..],
+        nibble,
+        &mut acc[acc_start as usize..],
+        4,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aes_128_ctr_offset() {
+        let key = [7u8; 16];
+        let mut full = [0u32; 40];
+        safe_aes_128_ctr(&mut full, 160, &key);
+
+        let mut tail = [0u32; 20];
+        safe_aes_128_ctr_offset(&mut tail, 80, 80, &key);
+        assert_eq!(tail, full[20..]);
+
+        // A length that is not a multiple of 4 leaves the rest of the last word untouched
+        let mut partial = [u32::MAX; 2];
+        safe_aes_128_ctr(&mut partial, 6, &key);
+        assert_eq!(partial[0], full[0]);
+        assert_eq!(partial[1] & 0xFFFF, full[1] & 0xFFFF);
+        assert_eq!(partial[1] >> 16, 0xFFFF);
+    }
+
+    #[test]
+    fn test_shake256_incremental() {
+        let input = b"incremental and one-shot SHAKE256";
+        let mut expected = [0u8; 64];
+        safe_shake256(&mut expected, 64, input, input.len() as u64);
+
+        let mut state = safe_shake256_inc_init();
+        safe_shake256_inc_absorb(&mut state, &input[..5]);
+        safe_shake256_inc_absorb(&mut state, &input[5..]);
+        safe_shake256_inc_finalize(&mut state);
+        let mut output = [0u8; 64];
+        safe_shake256_inc_squeeze(&mut output[..10], &mut state);
+        safe_shake256_inc_squeeze(&mut output[10..], &mut state);
+        safe_shake256_inc_ctx_release(&mut state);
+        assert_eq!(output, expected);
+    }
+}
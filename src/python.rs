@@ -0,0 +1,232 @@
+// Python bindings (PyO3) for the compiled parameter set. Keys, messages and signatures are passed
+// as bytes, errors raise `mayo.MayoError` (a subclass of ValueError). Build the extension module
+// with maturin, see pyproject.toml and tests/python.
+//
+// keygen and sign draw randomness from the NIST randomness source, which the module seeds from the
+// operating system on import. It is shared global state, so these calls keep holding the GIL.
+use pyo3::create_exception;
+use pyo3::exceptions::{PyOSError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyString};
+
+use crate::constants::{
+    CPK_BYTES, CSK_BYTES, DIGEST_BYTES, EPK_BYTES, ESK_BYTES, K, M, N, O, OID, PK_SEED_BYTES, Q,
+    SALT_BYTES, SIG_BYTES, SK_SEED_BYTES, VERSION,
+};
+use crate::crypto_primitives::safe_random_bytes_init;
+use crate::key_encoding;
+use crate::mayo_functionality::{
+    compact_key_gen, compact_key_gen_from_seed, expand_pk, sign_with_context, verify_with_context,
+    CompactPublicKey,
+};
+
+create_exception!(mayo, MayoError, PyValueError);
+
+const ENTROPY_INPUT_BYTES: usize = 48;
+
+fn to_py_err(error: &'static str) -> PyErr {
+    MayoError::new_err(error)
+}
+
+fn secret_key_from_bytes(secret_key: &[u8]) -> PyResult<[u8; CSK_BYTES]> {
+    secret_key
+        .try_into()
+        .map_err(|_| to_py_err("The secret key has incorrect length"))
+}
+
+fn key_pair<'py>(
+    py: Python<'py>,
+    cpk: &CompactPublicKey,
+    csk: &[u8; CSK_BYTES],
+) -> (Bound<'py, PyBytes>, Bound<'py, PyBytes>) {
+    (PyBytes::new(py, &cpk.to_bytes()), PyBytes::new(py, csk))
+}
+
+// Seeds the randomness source. Passing the same 48 bytes reproduces the same keys and signatures.
+#[pyfunction]
+fn seed_randomness(entropy_input: &[u8]) -> PyResult<()> {
+    let mut entropy_input: [u8; ENTROPY_INPUT_BYTES] = entropy_input
+        .try_into()
+        .map_err(|_| to_py_err("The entropy input must be 48 bytes"))?;
+    safe_random_bytes_init(&mut entropy_input, &[0u8; ENTROPY_INPUT_BYTES], 256);
+    Ok(())
+}
+
+fn seed_randomness_from_os() -> PyResult<()> {
+    let mut entropy_input = [0u8; ENTROPY_INPUT_BYTES];
+    getrandom::getrandom(&mut entropy_input).map_err(|error| {
+        PyOSError::new_err(format!(
+            "Could not get randomness from the operating system: {}",
+            error
+        ))
+    })?;
+    safe_random_bytes_init(&mut entropy_input, &[0u8; ENTROPY_INPUT_BYTES], 256);
+    Ok(())
+}
+
+// Returns (public_key, secret_key)
+#[pyfunction]
+fn keygen(py: Python<'_>) -> (Bound<'_, PyBytes>, Bound<'_, PyBytes>) {
+    let (cpk, csk) = compact_key_gen();
+    key_pair(py, &cpk, &csk)
+}
+
+// Returns (public_key, secret_key) for a given seed_sk, the secret key is the seed itself
+#[pyfunction]
+fn keygen_from_seed<'py>(
+    py: Python<'py>,
+    seed: &[u8],
+) -> PyResult<(Bound<'py, PyBytes>, Bound<'py, PyBytes>)> {
+    let (cpk, csk) = compact_key_gen_from_seed(secret_key_from_bytes(seed)?);
+    Ok(key_pair(py, &cpk, &csk))
+}
+
+// Recovers the public key of a secret key
+#[pyfunction]
+fn public_key<'py>(py: Python<'py>, secret_key: &[u8]) -> PyResult<Bound<'py, PyBytes>> {
+    let (cpk, _) = compact_key_gen_from_seed(secret_key_from_bytes(secret_key)?);
+    Ok(PyBytes::new(py, &cpk.to_bytes()))
+}
+
+#[pyfunction]
+#[pyo3(signature = (secret_key, message, context = None))]
+fn sign<'py>(
+    py: Python<'py>,
+    secret_key: &[u8],
+    message: &[u8],
+    context: Option<&[u8]>,
+) -> PyResult<Bound<'py, PyBytes>> {
+    let csk = secret_key_from_bytes(secret_key)?;
+    let signature =
+        sign_with_context(csk, message, context.unwrap_or_default()).map_err(to_py_err)?;
+    Ok(PyBytes::new(py, &signature))
+}
+
+// Returns whether the signature is valid. Raises MayoError only for a malformed public key.
+#[pyfunction]
+#[pyo3(signature = (public_key, message, signature, context = None))]
+fn verify(
+    py: Python<'_>,
+    public_key: &[u8],
+    message: &[u8],
+    signature: &[u8],
+    context: Option<&[u8]>,
+) -> PyResult<bool> {
+    let cpk = CompactPublicKey::from_bytes(public_key).map_err(to_py_err)?;
+    if signature.len() != SIG_BYTES {
+        return Ok(false);
+    }
+
+    // Verification does not use the randomness source
+    Ok(py.allow_threads(|| {
+        verify_with_context(
+            expand_pk(cpk),
+            signature,
+            message,
+            context.unwrap_or_default(),
+        )
+    }))
+}
+
+// DER/PEM encoding of keys with the provisional OID of the parameter set (see key_encoding)
+#[pyfunction]
+fn public_key_to_der<'py>(py: Python<'py>, public_key: &[u8]) -> PyResult<Bound<'py, PyBytes>> {
+    let cpk = CompactPublicKey::from_bytes(public_key).map_err(to_py_err)?;
+    let der =
+        key_encoding::public_key_to_der(&cpk, key_encoding::DEFAULT_OID).map_err(to_py_err)?;
+    Ok(PyBytes::new(py, &der))
+}
+
+#[pyfunction]
+fn public_key_to_pem<'py>(py: Python<'py>, public_key: &[u8]) -> PyResult<Bound<'py, PyString>> {
+    let cpk = CompactPublicKey::from_bytes(public_key).map_err(to_py_err)?;
+    let pem =
+        key_encoding::public_key_to_pem(&cpk, key_encoding::DEFAULT_OID).map_err(to_py_err)?;
+    Ok(PyString::new(py, &pem))
+}
+
+#[pyfunction]
+fn public_key_from_der<'py>(py: Python<'py>, der: &[u8]) -> PyResult<Bound<'py, PyBytes>> {
+    let cpk =
+        key_encoding::public_key_from_der(der, key_encoding::DEFAULT_OID).map_err(to_py_err)?;
+    Ok(PyBytes::new(py, &cpk.to_bytes()))
+}
+
+#[pyfunction]
+fn public_key_from_pem<'py>(py: Python<'py>, pem: &str) -> PyResult<Bound<'py, PyBytes>> {
+    let cpk =
+        key_encoding::public_key_from_pem(pem, key_encoding::DEFAULT_OID).map_err(to_py_err)?;
+    Ok(PyBytes::new(py, &cpk.to_bytes()))
+}
+
+#[pyfunction]
+fn secret_key_to_der<'py>(py: Python<'py>, secret_key: &[u8]) -> PyResult<Bound<'py, PyBytes>> {
+    let csk = secret_key_from_bytes(secret_key)?;
+    let der =
+        key_encoding::secret_key_to_der(&csk, key_encoding::DEFAULT_OID).map_err(to_py_err)?;
+    Ok(PyBytes::new(py, &der))
+}
+
+#[pyfunction]
+fn secret_key_to_pem<'py>(py: Python<'py>, secret_key: &[u8]) -> PyResult<Bound<'py, PyString>> {
+    let csk = secret_key_from_bytes(secret_key)?;
+    let pem =
+        key_encoding::secret_key_to_pem(&csk, key_encoding::DEFAULT_OID).map_err(to_py_err)?;
+    Ok(PyString::new(py, &pem))
+}
+
+#[pyfunction]
+fn secret_key_from_der<'py>(py: Python<'py>, der: &[u8]) -> PyResult<Bound<'py, PyBytes>> {
+    let csk =
+        key_encoding::secret_key_from_der(der, key_encoding::DEFAULT_OID).map_err(to_py_err)?;
+    Ok(PyBytes::new(py, &csk))
+}
+
+#[pyfunction]
+fn secret_key_from_pem<'py>(py: Python<'py>, pem: &str) -> PyResult<Bound<'py, PyBytes>> {
+    let csk =
+        key_encoding::secret_key_from_pem(pem, key_encoding::DEFAULT_OID).map_err(to_py_err)?;
+    Ok(PyBytes::new(py, &csk))
+}
+
+#[pymodule]
+#[pyo3(name = "mayo")]
+fn mayo_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    seed_randomness_from_os()?;
+
+    module.add("MayoError", module.py().get_type::<MayoError>())?;
+
+    // Parameter set constants
+    module.add("VERSION", VERSION)?;
+    module.add("OID", OID)?;
+    module.add("N", N)?;
+    module.add("M", M)?;
+    module.add("O", O)?;
+    module.add("K", K)?;
+    module.add("Q", Q)?;
+    module.add("CPK_BYTES", CPK_BYTES)?;
+    module.add("CSK_BYTES", CSK_BYTES)?;
+    module.add("EPK_BYTES", EPK_BYTES)?;
+    module.add("ESK_BYTES", ESK_BYTES)?;
+    module.add("SIG_BYTES", SIG_BYTES)?;
+    module.add("SALT_BYTES", SALT_BYTES)?;
+    module.add("DIGEST_BYTES", DIGEST_BYTES)?;
+    module.add("PK_SEED_BYTES", PK_SEED_BYTES)?;
+    module.add("SK_SEED_BYTES", SK_SEED_BYTES)?;
+
+    module.add_function(wrap_pyfunction!(seed_randomness, module)?)?;
+    module.add_function(wrap_pyfunction!(keygen, module)?)?;
+    module.add_function(wrap_pyfunction!(keygen_from_seed, module)?)?;
+    module.add_function(wrap_pyfunction!(public_key, module)?)?;
+    module.add_function(wrap_pyfunction!(sign, module)?)?;
+    module.add_function(wrap_pyfunction!(verify, module)?)?;
+    module.add_function(wrap_pyfunction!(public_key_to_der, module)?)?;
+    module.add_function(wrap_pyfunction!(public_key_to_pem, module)?)?;
+    module.add_function(wrap_pyfunction!(public_key_from_der, module)?)?;
+    module.add_function(wrap_pyfunction!(public_key_from_pem, module)?)?;
+    module.add_function(wrap_pyfunction!(secret_key_to_der, module)?)?;
+    module.add_function(wrap_pyfunction!(secret_key_to_pem, module)?)?;
+    module.add_function(wrap_pyfunction!(secret_key_from_der, module)?)?;
+    module.add_function(wrap_pyfunction!(secret_key_from_pem, module)?)?;
+    Ok(())
+}
@@ -1,103 +1,185 @@
 use crate::constants::{K, M, O};
-use crate::finite_field::{add, inv, mul, sub};
-use crate::{matrix_vec_mul, vec_add};
+use crate::finite_field::{inv, mul_packed, Gf16};
+use crate::matrix::{Matrix, Vector};
+
+pub const COLS: usize = K * O + 1;
+// Words of 16 nibble-packed elements per row of (A | y)
+pub const WORDS: usize = COLS.div_ceil(16);
+
+// Rows of (A | y) packed 16 elements to a u64, element j in bits 4 * (j % 16) of word j / 16
+pub type PackedRows = [[u64; WORDS]; M];
 
 // MAYO Algorithm 1: Echelon Form
-// Function to perform the echelon form algorithm on matrix B.
-pub fn echelon_form(mut b: [[u8; K * O + 1]; M]) -> [[u8; K * O + 1]; M] {
-    const ROWS: usize = M;
-    const COLS: usize = K * O + 1;
+// Function to perform the echelon form algorithm on matrix B. The rows are packed, so that scaling
+// and eliminating a row works on 16 elements at once, like `ef` of MAYO-C. The result is the same
+// as row_echelon of linear_algebra.rs on the unpacked rows.
+pub fn echelon_form(b: Matrix<M, COLS>) -> Matrix<M, COLS> {
+    let mut rows = pack_rows(&b);
+    eliminate(&mut rows);
+    unpack_rows(&rows)
+}
+
+fn eliminate(rows: &mut PackedRows) {
     let mut pivot_row = 0;
     let mut pivot_column = 0;
-
-    while pivot_row < ROWS && pivot_column < COLS {
+    while pivot_row < M && pivot_column < COLS {
         // Find the first possible pivot in the current column
-        let mut next_pivot_row = None;
-        for i in pivot_row..ROWS {
-            if b[i][pivot_column] != 0 {
-                next_pivot_row = Some(i);
-                break; // Optimized to break early if pivot is found
-            }
-        }
-        let next_pivot_row = match next_pivot_row {
-            Some(row) => row,
-            None => {
-                pivot_column += 1; // Move to next column if there is no pivot
-                continue;
-            }
+        let Some(next_pivot_row) =
+            (pivot_row..M).find(|&i| packed_element(&rows[i], pivot_column) != 0)
+        else {
+            pivot_column += 1; // Move to next column if there is no pivot
+            continue;
         };
-        b.swap(pivot_row, next_pivot_row); // Swap rows
+        rows.swap(pivot_row, next_pivot_row);
+
+        // The entries left of the pivot column are zero in this and the rows below, so the row
+        // operations can start at the word holding the pivot
+        let first_word = pivot_column / 16;
 
         // Make the leading entry a "1" by multiplying the row by the inverse of the pivot
-        let inv_idx = inv(b[pivot_row][pivot_column]);
-        for j in pivot_column..COLS {
-            b[pivot_row][j] = mul(inv_idx, b[pivot_row][j]);
+        let (upper, lower) = rows.split_at_mut(pivot_row + 1);
+        let pivot = &mut upper[pivot_row];
+        let inv_pivot = inv(packed_element(pivot, pivot_column));
+        for word in &mut pivot[first_word..] {
+            *word = mul_packed(*word, inv_pivot);
         }
 
         // Eliminate entries below the pivot
-        for i in pivot_row + 1..ROWS {
-            // From next pivot row to m - 1
-            let factor = b[i][pivot_column];
-            for j in pivot_column..COLS {
-                let mult = mul(factor, b[pivot_row][j]);
-                let res = sub(b[i][j], mult);
-                b[i][j] = res // b[i][j] - (factor * b[pivot_row][j]);
+        for row in lower {
+            let factor = packed_element(row, pivot_column);
+            for (word, pivot_word) in row[first_word..].iter_mut().zip(&pivot[first_word..]) {
+                *word ^= mul_packed(*pivot_word, factor);
             }
         }
         pivot_row += 1;
         pivot_column += 1;
     }
+}
+
+fn pack_rows(b: &Matrix<M, COLS>) -> PackedRows {
+    let mut rows = [[0u64; WORDS]; M];
+    for (packed, row) in rows.iter_mut().zip(&b.0) {
+        for (j, element) in row.iter().enumerate() {
+            packed[j / 16] |= (element.value() as u64) << (4 * (j % 16));
+        }
+    }
+    rows
+}
+
+pub fn unpack_rows(rows: &PackedRows) -> Matrix<M, COLS> {
+    let mut b = Matrix::zero();
+    for (row, packed) in b.0.iter_mut().zip(rows) {
+        for (j, element) in row.iter_mut().enumerate() {
+            *element = Gf16::from_nibble(packed_element(packed, j));
+        }
+    }
     b
 }
 
+fn packed_element(row: &[u64; WORDS], j: usize) -> u8 {
+    ((row[j / 16] >> (4 * (j % 16))) & 0xf) as u8
+}
+
 // MAYO Algorithm 2: Sample Solution
 // Function to solve the equation Ax = y in GF(16) using gaussian elimination.
 pub fn sample_solution(
-    a: [[u8; K * O]; M],
-    y: [u8; M],
-    r: [u8; K * O],
-) -> Result<[u8; K * O], &'static str> {
-    let mut x: [u8; K * O] = r;
-    let mut temp = matrix_vec_mul!(a, x, M, K * O); //  (m x K*O) * (K*O x 1) = (m x 1)
-    temp = vec_add!(temp, y, M); // Add same as subtracting in GF(16)
+    a: Matrix<M, { K * O }>,
+    y: Vector<M>,
+    r: Vector<{ K * O }>,
+) -> Result<Vector<{ K * O }>, &'static str> {
+    let mut system = Matrix::<M, COLS>::zero();
+    for i in 0..M {
+        system[i][..K * O].copy_from_slice(&a[i]);
+        system[i][K * O] = y[i];
+    }
+    sample_solution_packed(pack_rows(&system), r)
+}
 
-    let mut pre_ech_a: [[u8; K * O + 1]; M] = [[0u8; K * O + 1]; M];
+// Sample Solution on (A | y) given as packed rows, as built by `linear_system` in sign
+pub fn sample_solution_packed(
+    mut system: PackedRows,
+    r: Vector<{ K * O }>,
+) -> Result<Vector<{ K * O }>, &'static str> {
+    let mut x = r;
 
-    for i in 0..M {
-        pre_ech_a[i][..K * O].copy_from_slice(&a[i]);
-        pre_ech_a[i][K * O] = temp[i];
+    // y = y - A * r
+    for row in system.iter_mut() {
+        let a_r: Gf16 = (0..K * O)
+            .map(|j| Gf16::from_nibble(packed_element(row, j)) * r[j])
+            .sum();
+        row[K * O / 16] ^= (a_r.value() as u64) << (4 * (K * O % 16));
     }
 
     // Put (A | y) in echelon form with leading 1's.
-    let a: [[u8; K * O + 1]; M] = echelon_form(pre_ech_a);
+    eliminate(&mut system);
+    let a = unpack_rows(&system);
 
     // Split the matrix into A and y
-    let mut a_ech: [[u8; K * O]; M] = [[0; K * O]; M];
-    let mut y_ech: [u8; M] = [0; M];
-    for (i, row) in a.iter().enumerate() {
+    let mut a_ech = Matrix::<M, { K * O }>::zero();
+    let mut y_ech = Vector::<M>::zero();
+    for (i, row) in a.0.iter().enumerate() {
         a_ech[i].copy_from_slice(&row[..K * O]);
         y_ech[i] = row[K * O];
     }
 
     // Check if the matrix A has full rank (E.g. no full row of zeros in the echelon form)
-    if a_ech[M - 1].iter().all(|&i| i == 0) {
+    if a_ech[M - 1].iter().all(|i| i.is_zero()) {
         return Err("The matrix A does not have full rank. No solution is found");
     }
 
     // Back-substitution
     for r in (0..M).rev() {
         // Let c be the index of first non-zero element of A[r, :]
-        let c = a_ech[r].iter().position(|&i| i != 0).unwrap();
-        x[c] = add(x[c], y_ech[r]);
+        let c = a_ech[r].iter().position(|i| !i.is_zero()).unwrap();
+        x[c] += y_ech[r];
+
+        // y_ech = y_ech - y_ech[r] * A[:, c]
+        let coefficient = y_ech[r];
+        y_ech -= a_ech.column(c) * coefficient;
+    }
+    Ok(x)
+}
 
-        // Prepare for updating y_ech
-        let mut temp_mult: [u8; M] = [0; M]; // Initialize an array with zeros
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linear_algebra::row_echelon;
+    use proptest::prelude::*;
 
-        for (i, row) in a_ech.iter().enumerate() {
-            temp_mult[i] = mul(y_ech[r], row[c]);
-        }
+    // Random rows, with every row after the first few a combination of those, so that the
+    // echelon form also has zero rows and columns without a pivot
+    fn matrix() -> impl Strategy<Value = Matrix<M, COLS>> {
+        (
+            prop::collection::vec(0u8..16, M * COLS),
+            prop::collection::vec(0u8..16, M * M),
+            1..=M,
+        )
+            .prop_map(|(values, coefficients, rank)| {
+                let mut b = Matrix::zero();
+                for (i, row) in b.0.iter_mut().enumerate() {
+                    for (j, element) in row.iter_mut().enumerate() {
+                        *element = Gf16::from_nibble(values[i * COLS + j]);
+                    }
+                }
+                for i in rank..M {
+                    let mut row = Vector::zero();
+                    for k in 0..rank {
+                        row += Vector(b[k]) * Gf16::from_nibble(coefficients[i * M + k]);
+                    }
+                    b[i] = row.0;
+                }
+                b
+            })
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
 
-        vec_add!(y_ech, temp_mult, M); // Add same as subtracting in GF(16)
+        #[test]
+        fn test_echelon_form_matches_unpacked(b in matrix()) {
+            let mut expected = b;
+            row_echelon(&mut expected.0);
+            prop_assert_eq!(echelon_form(b), expected);
+        }
     }
-    Ok(x)
 }
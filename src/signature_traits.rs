@@ -0,0 +1,215 @@
+// Implementations of the RustCrypto `signature` traits for MAYO, so MAYO can be used
+// wherever a generic `Signer`/`Verifier` is expected.
+use signature::rand_core::CryptoRngCore;
+use signature::{Error, Keypair, RandomizedSigner, SignatureEncoding, Signer, Verifier};
+
+use crate::constants::{CPK_BYTES, CSK_BYTES, R_BYTES, SIG_BYTES, SK_SEED_BYTES};
+use crate::mayo_functionality::{
+    compact_key_gen, compact_key_gen_from_seed, expand_pk, message_digest, sign,
+    sign_digest_with_randomness, verify, CompactPublicKey,
+};
+
+// MAYO signature (encoded solution s followed by the salt)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Signature([u8; SIG_BYTES]);
+
+// Compact secret key together with its public key
+#[derive(Clone)]
+pub struct SigningKey {
+    csk: [u8; CSK_BYTES],
+    verifying_key: VerifyingKey,
+}
+
+// Compact public key
+#[derive(Clone)]
+pub struct VerifyingKey {
+    cpk: CompactPublicKey,
+}
+
+impl SigningKey {
+    // Generate a new key pair using the NIST randomness source
+    pub fn generate() -> Self {
+        let (cpk, csk) = compact_key_gen();
+        SigningKey {
+            csk,
+            verifying_key: VerifyingKey { cpk },
+        }
+    }
+
+    // Recreate the key pair from a compact secret key (seed_sk)
+    pub fn from_bytes(csk: [u8; CSK_BYTES]) -> Self {
+        let sk_seed: [u8; SK_SEED_BYTES] = csk;
+        let (cpk, csk) = compact_key_gen_from_seed(sk_seed);
+        SigningKey {
+            csk,
+            verifying_key: VerifyingKey { cpk },
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; CSK_BYTES] {
+        self.csk
+    }
+}
+
+impl VerifyingKey {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let cpk = CompactPublicKey::from_bytes(bytes).map_err(|_| Error::new())?;
+        Ok(VerifyingKey { cpk })
+    }
+
+    pub fn to_bytes(&self) -> [u8; CPK_BYTES] {
+        self.cpk.to_bytes()
+    }
+}
+
+impl From<CompactPublicKey> for VerifyingKey {
+    fn from(cpk: CompactPublicKey) -> Self {
+        VerifyingKey { cpk }
+    }
+}
+
+impl Keypair for SigningKey {
+    type VerifyingKey = VerifyingKey;
+
+    fn verifying_key(&self) -> VerifyingKey {
+        self.verifying_key.clone()
+    }
+}
+
+impl Signer<Signature> for SigningKey {
+    fn try_sign(&self, msg: &[u8]) -> Result<Signature, Error> {
+        Ok(Signature(sign(self.csk, msg)))
+    }
+}
+
+impl RandomizedSigner<Signature> for SigningKey {
+    // The salt randomness r is taken from `rng` instead of the NIST randomness source
+    fn try_sign_with_rng(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        msg: &[u8],
+    ) -> Result<Signature, Error> {
+        let mut r = [0u8; R_BYTES];
+        rng.try_fill_bytes(&mut r).map_err(|_| Error::new())?;
+
+        Ok(Signature(sign_digest_with_randomness(
+            self.csk,
+            &message_digest(msg),
+            r,
+        )))
+    }
+}
+
+impl Verifier<Signature> for VerifyingKey {
+    fn verify(&self, msg: &[u8], signature: &Signature) -> Result<(), Error> {
+        if verify(expand_pk(self.cpk.clone()), &signature.0, msg) {
+            Ok(())
+        } else {
+            Err(Error::new())
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for Signature {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes: [u8; SIG_BYTES] = bytes.try_into().map_err(|_| Error::new())?;
+        Ok(Signature(bytes))
+    }
+}
+
+impl From<Signature> for [u8; SIG_BYTES] {
+    fn from(signature: Signature) -> Self {
+        signature.0
+    }
+}
+
+impl SignatureEncoding for Signature {
+    type Repr = [u8; SIG_BYTES];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use signature::rand_core::{CryptoRng, RngCore};
+
+    // Deterministic RNG for tests (NOT secure)
+    struct CounterRng(u8);
+
+    impl RngCore for CounterRng {
+        fn next_u32(&mut self) -> u32 {
+            let mut bytes = [0u8; 4];
+            self.fill_bytes(&mut bytes);
+            u32::from_le_bytes(bytes)
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            let mut bytes = [0u8; 8];
+            self.fill_bytes(&mut bytes);
+            u64::from_le_bytes(bytes)
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            for byte in dest.iter_mut() {
+                *byte = self.0;
+                self.0 = self.0.wrapping_add(1);
+            }
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), signature::rand_core::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    impl CryptoRng for CounterRng {}
+
+    #[test]
+    fn test_signer_and_verifier() {
+        let signing_key = SigningKey::generate();
+        let verifying_key = signing_key.verifying_key();
+
+        let signature: Signature = signing_key.sign(b"message");
+
+        assert!(verifying_key.verify(b"message", &signature).is_ok());
+        assert!(verifying_key.verify(b"other message", &signature).is_err());
+    }
+
+    #[test]
+    fn test_randomized_signer_is_deterministic_in_rng() {
+        let signing_key = SigningKey::generate();
+        let verifying_key = signing_key.verifying_key();
+
+        let signature_1: Signature = signing_key.sign_with_rng(&mut CounterRng(0), b"message");
+        let signature_2: Signature = signing_key.sign_with_rng(&mut CounterRng(0), b"message");
+        let signature_3: Signature = signing_key.sign_with_rng(&mut CounterRng(1), b"message");
+
+        assert_eq!(signature_1, signature_2);
+        assert_ne!(signature_1, signature_3);
+        assert!(verifying_key.verify(b"message", &signature_1).is_ok());
+        assert!(verifying_key.verify(b"message", &signature_3).is_ok());
+    }
+
+    #[test]
+    fn test_key_and_signature_encoding() {
+        let signing_key = SigningKey::generate();
+        let restored = SigningKey::from_bytes(signing_key.to_bytes());
+        assert_eq!(
+            restored.verifying_key().to_bytes(),
+            signing_key.verifying_key().to_bytes()
+        );
+
+        let verifying_key = VerifyingKey::from_bytes(&signing_key.verifying_key().to_bytes())
+            .expect("Public key has correct length");
+        assert!(VerifyingKey::from_bytes(&[0u8; CPK_BYTES - 1]).is_err());
+
+        let signature: Signature = restored.sign(b"message");
+        let encoded = signature.to_bytes();
+        assert_eq!(encoded.len(), SIG_BYTES);
+
+        let decoded = Signature::try_from(encoded.as_slice()).unwrap();
+        assert!(verifying_key.verify(b"message", &decoded).is_ok());
+        assert!(Signature::try_from(&encoded[1..]).is_err());
+    }
+}
@@ -0,0 +1,231 @@
+// Traces of the named intermediate values of keygen, sign and verify (feature `trace`), to find the
+// step where a port or another implementation diverges:
+//
+//   let (signature, trace) = trace::capture(|| sign(csk, message));
+//   print!("{}", trace.to_text());
+//
+// Values are recorded per thread, in the order they are computed, and encoded as in the MAYO
+// specification and MAYO-C rather than in this crate's bitsliced layout: vectors and matrices over
+// GF(16) with two elements per byte (first element in the low nibble, matrices row by row), and
+// P1, L and P3 as their m-vectors in the order of the matrix entries (the upper triangle for P1
+// and P3), each as m elements packed the same way.
+// The recorded names are
+//   compact_key_gen_from_seed: pk_seed, O, P3
+//   expand_sk:                 O, P1, L
+//   sign:                      salt, t, then for every attempt ctr, v_i, M_i, A, y (A and y
+//                              reduced mod f(z)) and x once a solution is found, and finally s
+//   verify:                    s, t, y
+use std::cell::RefCell;
+
+use crate::constants::M;
+use crate::crypto_primitives::safe_shake256;
+use crate::decode_bit_sliced_array;
+use crate::utils::{bytes_to_hex_string, hex_string_to_bytes};
+
+// Values longer than this are written as a digest by `to_digest_text`
+pub const DIGEST_THRESHOLD: usize = 16384;
+
+// Name suffix of values written as their 32 byte SHAKE256 digest
+const DIGEST_SUFFIX: &str = ".shake256";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Trace {
+    pub entries: Vec<(String, Vec<u8>)>,
+}
+
+thread_local! {
+    static ACTIVE: RefCell<Option<Trace>> = const { RefCell::new(None) };
+}
+
+// Runs `f` and returns its result together with the values recorded on this thread meanwhile
+pub fn capture<R>(f: impl FnOnce() -> R) -> (R, Trace) {
+    let outer = ACTIVE.with(|active| active.replace(Some(Trace::default())));
+    let result = f();
+    let trace = ACTIVE
+        .with(|active| active.replace(outer))
+        .unwrap_or_default();
+    (result, trace)
+}
+
+pub fn is_active() -> bool {
+    ACTIVE.with(|active| active.borrow().is_some())
+}
+
+// Appends a value to the trace being captured on this thread, if any
+pub fn record(name: impl Into<String>, value: Vec<u8>) {
+    ACTIVE.with(|active| {
+        if let Some(trace) = active.borrow_mut().as_mut() {
+            trace.entries.push((name.into(), value));
+        }
+    });
+}
+
+// Field elements (one per byte) with two elements per byte, first element in the low nibble
+pub fn encode_elements(elements: &[u8]) -> Vec<u8> {
+    elements
+        .chunks(2)
+        .map(|pair| pair[0] | pair.get(1).map_or(0, |second| second << 4))
+        .collect()
+}
+
+// Bitsliced m-vectors (M / 8 words each) as their elements, m-vector after m-vector
+pub fn encode_m_vectors(words: &[u32]) -> Vec<u8> {
+    words
+        .chunks(M / 8)
+        .flat_map(|m_vector| encode_elements(&decode_bit_sliced_array!(m_vector)))
+        .collect()
+}
+
+fn digest(value: &[u8]) -> Vec<u8> {
+    let mut digest = vec![0u8; 32];
+    safe_shake256(&mut digest, 32, value, value.len() as u64);
+    digest
+}
+
+impl Trace {
+    // First value recorded under `name`
+    pub fn get(&self, name: &str) -> Option<&[u8]> {
+        self.entries
+            .iter()
+            .find(|(entry_name, _)| entry_name == name)
+            .map(|(_, value)| value.as_slice())
+    }
+
+    // One `name = HEX` line per value
+    pub fn to_text(&self) -> String {
+        self.entries
+            .iter()
+            .map(|(name, value)| format!("{} = {}\n", name, bytes_to_hex_string(value, false)))
+            .collect()
+    }
+
+    // Like `to_text`, but values longer than DIGEST_THRESHOLD are written as
+    // `name.shake256 = digest`, for dumps where P1 would dominate the size. The digest is the
+    // 32 byte SHAKE256 of the value in the encoding above.
+    pub fn to_digest_text(&self) -> String {
+        self.entries
+            .iter()
+            .map(|(name, value)| {
+                if value.len() > DIGEST_THRESHOLD {
+                    let digest = bytes_to_hex_string(&digest(value), false);
+                    format!("{}{} = {}\n", name, DIGEST_SUFFIX, digest)
+                } else {
+                    format!("{} = {}\n", name, bytes_to_hex_string(value, false))
+                }
+            })
+            .collect()
+    }
+
+    // Reads the output of `to_text` or `to_digest_text`. Empty lines and `#` comments are skipped.
+    pub fn parse(text: &str) -> Result<Trace, &'static str> {
+        let mut entries = Vec::new();
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (name, value) = line
+                .split_once(" = ")
+                .or_else(|| line.strip_suffix(" =").map(|name| (name, "")))
+                .ok_or("Expected `name = value`")?;
+            if value.len() % 2 != 0 || !value.bytes().all(|c| c.is_ascii_hexdigit()) {
+                return Err("The value is not a hex string");
+            }
+            entries.push((name.to_string(), hex_string_to_bytes(value)));
+        }
+        Ok(Trace { entries })
+    }
+
+    // Compares with an expected trace entry by entry. Expected values may be digests as written
+    // by `to_digest_text`. Describes the first entry that differs.
+    pub fn compare(&self, expected: &Trace) -> Result<(), String> {
+        for (index, (expected_entry, entry)) in
+            expected.entries.iter().zip(&self.entries).enumerate()
+        {
+            let (expected_name, expected_value) = expected_entry;
+            let (name, value) = entry;
+
+            let (matches_name, value) = match expected_name.strip_suffix(DIGEST_SUFFIX) {
+                Some(digested_name) => (digested_name == name, digest(value)),
+                None => (expected_name == name, value.clone()),
+            };
+            if !matches_name {
+                return Err(format!(
+                    "Entry {}: expected {}, got {}",
+                    index, expected_name, name
+                ));
+            }
+            if &value != expected_value {
+                let position = value
+                    .iter()
+                    .zip(expected_value)
+                    .position(|(a, b)| a != b)
+                    .unwrap_or(value.len().min(expected_value.len()));
+                return Err(format!(
+                    "Entry {} ({}) differs at byte {} of {}",
+                    index,
+                    expected_name,
+                    position,
+                    expected_value.len()
+                ));
+            }
+        }
+
+        if self.entries.len() != expected.entries.len() {
+            return Err(format!(
+                "Expected {} entries, got {}",
+                expected.entries.len(),
+                self.entries.len()
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_and_text() {
+        assert!(!is_active());
+        record("ignored", vec![1]);
+
+        let (result, trace) = capture(|| {
+            record("a", vec![0x12, 0xAB]);
+            record("empty", vec![]);
+            record("large", vec![7u8; DIGEST_THRESHOLD + 1]);
+            assert!(is_active());
+            42
+        });
+        assert_eq!(result, 42);
+        assert!(!is_active());
+        assert_eq!(trace.entries.len(), 3);
+        assert_eq!(trace.get("a"), Some(&[0x12, 0xAB][..]));
+
+        let parsed = Trace::parse(&trace.to_text()).unwrap();
+        assert_eq!(parsed, trace);
+        assert_eq!(trace.compare(&parsed), Ok(()));
+
+        // Digested values compare against the full value
+        let digested = Trace::parse(&trace.to_digest_text()).unwrap();
+        assert_eq!(digested.entries[2].0, "large.shake256");
+        assert_eq!(trace.compare(&digested), Ok(()));
+
+        let mut changed = trace.clone();
+        changed.entries[2].1[5] ^= 1;
+        assert!(changed.compare(&digested).unwrap_err().contains("Entry 2"));
+        changed.entries[0].1[1] ^= 1;
+        assert!(changed.compare(&parsed).unwrap_err().contains("at byte 1"));
+
+        assert_eq!(encode_elements(&[0x1, 0x2, 0x3]), vec![0x21, 0x03]);
+
+        // Element 0 has its lowest bit set in the first plane, element 1 its highest bit in the last
+        let mut m_vector = [0u32; M / 8];
+        m_vector[0] = 0x1;
+        m_vector[3 * M / 32] = 0x2;
+        let mut expected = vec![0u8; M / 2];
+        expected[0] = 0x81;
+        assert_eq!(encode_m_vectors(&m_vector), expected);
+        assert_eq!(encode_m_vectors(&[m_vector, m_vector].concat()).len(), M);
+    }
+}
@@ -28,6 +28,16 @@ pub fn set_seed_for_test(mut entropy_input: Vec<u8>) {
     );
 }
 
+// Random words, e.g. bitsliced elements, for tests and benchmarks
+#[cfg(any(test, feature = "bench"))]
+pub fn random_words<const LEN: usize>() -> [u32; LEN] {
+    use crate::crypto_primitives::safe_random_bytes;
+
+    let mut bytes = vec![0u8; 4 * LEN];
+    safe_random_bytes(&mut bytes, 4 * LEN as u64);
+    std::array::from_fn(|i| u32::from_le_bytes(bytes[4 * i..4 * i + 4].try_into().unwrap()))
+}
+
 pub fn write_u32_array_to_file_int(filename: &str, data: &[u32]) -> Result<()> {
     let mut file = File::create(filename)?;
 
@@ -0,0 +1,135 @@
+// WebAssembly bindings (wasm-bindgen) for the compiled parameter set, e.g. for verifying signed
+// documents in the browser. Build with
+//
+//   wasm-pack build --target web --no-default-features --features wasm,mayo1
+//
+// Keys, messages and signatures are Uint8Arrays. There is no randomness source in wasm32, so
+// keygen and sign take their randomness from the caller (e.g. crypto.getRandomValues).
+// Malformed keys and randomness throw an Error, an invalid signature makes verify return false.
+use wasm_bindgen::prelude::*;
+
+use crate::constants::{CPK_BYTES, CSK_BYTES, R_BYTES, SIG_BYTES, SK_SEED_BYTES, VERSION};
+use crate::mayo_functionality::{
+    compact_key_gen_from_seed, expand_pk, sign_digest_with_randomness, verify_digest,
+    CompactPublicKey,
+};
+use crate::message_hasher::MessageHasher;
+
+#[wasm_bindgen]
+pub struct KeyPair {
+    public_key: Vec<u8>,
+    secret_key: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl KeyPair {
+    #[wasm_bindgen(getter, js_name = publicKey)]
+    pub fn public_key(&self) -> Vec<u8> {
+        self.public_key.clone()
+    }
+
+    #[wasm_bindgen(getter, js_name = secretKey)]
+    pub fn secret_key(&self) -> Vec<u8> {
+        self.secret_key.clone()
+    }
+}
+
+// Name of the compiled parameter set, e.g. "MAYO_1"
+#[wasm_bindgen]
+pub fn version() -> String {
+    VERSION.to_string()
+}
+
+#[wasm_bindgen(js_name = publicKeyBytes)]
+pub fn public_key_bytes() -> usize {
+    CPK_BYTES
+}
+
+#[wasm_bindgen(js_name = secretKeyBytes)]
+pub fn secret_key_bytes() -> usize {
+    CSK_BYTES
+}
+
+#[wasm_bindgen(js_name = signatureBytes)]
+pub fn signature_bytes() -> usize {
+    SIG_BYTES
+}
+
+// Number of random bytes keygen expects
+#[wasm_bindgen(js_name = keygenRandomnessBytes)]
+pub fn keygen_randomness_bytes() -> usize {
+    SK_SEED_BYTES
+}
+
+// Number of random bytes sign expects
+#[wasm_bindgen(js_name = signRandomnessBytes)]
+pub fn sign_randomness_bytes() -> usize {
+    R_BYTES
+}
+
+// Key pair from SK_SEED_BYTES random bytes, which become the secret key
+#[wasm_bindgen]
+pub fn keygen(randomness: &[u8]) -> Result<KeyPair, JsError> {
+    let sk_seed = randomness
+        .try_into()
+        .map_err(|_| JsError::new("The keygen randomness has incorrect length"))?;
+    let (cpk, csk) = compact_key_gen_from_seed(sk_seed);
+
+    Ok(KeyPair {
+        public_key: cpk.to_bytes().to_vec(),
+        secret_key: csk.to_vec(),
+    })
+}
+
+// Recovers the public key of a secret key
+#[wasm_bindgen(js_name = publicKey)]
+pub fn public_key(secret_key: &[u8]) -> Result<Vec<u8>, JsError> {
+    let csk = secret_key
+        .try_into()
+        .map_err(|_| JsError::new("The secret key has incorrect length"))?;
+    let (cpk, _) = compact_key_gen_from_seed(csk);
+    Ok(cpk.to_bytes().to_vec())
+}
+
+// Signs `message` with R_BYTES bytes of caller randomness. The optional context is at most 255
+// bytes, see `sign_with_context`.
+#[wasm_bindgen]
+pub fn sign(
+    secret_key: &[u8],
+    message: &[u8],
+    randomness: &[u8],
+    context: Option<Vec<u8>>,
+) -> Result<Vec<u8>, JsError> {
+    let csk = secret_key
+        .try_into()
+        .map_err(|_| JsError::new("The secret key has incorrect length"))?;
+    let r = randomness
+        .try_into()
+        .map_err(|_| JsError::new("The sign randomness has incorrect length"))?;
+
+    let mut hasher =
+        MessageHasher::with_context(&context.unwrap_or_default()).map_err(JsError::new)?;
+    hasher.update(message);
+    Ok(sign_digest_with_randomness(csk, &hasher.finalize(), r).to_vec())
+}
+
+// Verifies a signature of `message`, optionally bound to a context as in `verify_with_context`
+#[wasm_bindgen]
+pub fn verify(
+    public_key: &[u8],
+    message: &[u8],
+    signature: &[u8],
+    context: Option<Vec<u8>>,
+) -> Result<bool, JsError> {
+    let cpk = CompactPublicKey::from_bytes(public_key).map_err(JsError::new)?;
+    if signature.len() != SIG_BYTES {
+        return Ok(false);
+    }
+
+    let mut hasher = match MessageHasher::with_context(&context.unwrap_or_default()) {
+        Ok(hasher) => hasher,
+        Err(_) => return Ok(false), // No valid signature exists for an invalid context
+    };
+    hasher.update(message);
+    Ok(verify_digest(expand_pk(cpk), signature, &hasher.finalize()))
+}
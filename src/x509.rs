@@ -0,0 +1,348 @@
+// X.509 certificates and PKCS#10 certificate requests signed with MAYO.
+// The DER encoding of the TBSCertificate (or CertificationRequestInfo) is signed with `sign`
+// and checked with `verify`. The signatureAlgorithm uses the same OID as the keys (see
+// `key_encoding`) and has no parameters.
+use std::time::SystemTime;
+
+use x509_cert::certificate::{Certificate, TbsCertificate, Version};
+use x509_cert::der::asn1::{BitString, OctetString};
+use x509_cert::der::oid::AssociatedOid;
+use x509_cert::der::{Decode, Encode};
+use x509_cert::ext::pkix::BasicConstraints;
+use x509_cert::ext::Extension;
+use x509_cert::name::Name;
+use x509_cert::request::{CertReq, CertReqInfo};
+use x509_cert::serial_number::SerialNumber;
+use x509_cert::spki::{AlgorithmIdentifierOwned, ObjectIdentifier, SubjectPublicKeyInfoOwned};
+use x509_cert::time::Validity;
+
+use crate::constants::{CSK_BYTES, SIG_BYTES};
+use crate::key_encoding::{public_key_from_der, public_key_to_der};
+use crate::mayo_functionality::{expand_pk, sign, verify, CompactPublicKey};
+
+// Fields of a certificate chosen by the issuer
+pub struct CertificateParams {
+    pub serial_number: u64,
+    pub subject: Name,
+    pub validity: Validity,
+    pub is_ca: bool, // Adds a critical basicConstraints extension with cA = true
+}
+
+// Issues a certificate for `subject_pk` signed by the issuer's compact secret key
+pub fn issue_certificate(
+    params: CertificateParams,
+    subject_pk: &CompactPublicKey,
+    issuer: &Name,
+    issuer_csk: [u8; CSK_BYTES],
+    oid: ObjectIdentifier,
+) -> Result<Certificate, &'static str> {
+    let extensions = if params.is_ca {
+        let basic_constraints = BasicConstraints {
+            ca: true,
+            path_len_constraint: None,
+        };
+        Some(vec![Extension {
+            extn_id: BasicConstraints::OID,
+            critical: true,
+            extn_value: OctetString::new(basic_constraints.to_der().map_err(|_| ENCODE_ERROR)?)
+                .map_err(|_| ENCODE_ERROR)?,
+        }])
+    } else {
+        None
+    };
+
+    let tbs_certificate = TbsCertificate {
+        version: Version::V3,
+        serial_number: SerialNumber::from(params.serial_number),
+        signature: algorithm_identifier(oid),
+        issuer: issuer.clone(),
+        validity: params.validity,
+        subject: params.subject,
+        subject_public_key_info: subject_public_key_info(subject_pk, oid)?,
+        issuer_unique_id: None,
+        subject_unique_id: None,
+        extensions,
+    };
+
+    let tbs_der = tbs_certificate.to_der().map_err(|_| ENCODE_ERROR)?;
+    let signature = sign(issuer_csk, &tbs_der);
+
+    Ok(Certificate {
+        tbs_certificate,
+        signature_algorithm: algorithm_identifier(oid),
+        signature: BitString::from_bytes(&signature).map_err(|_| ENCODE_ERROR)?,
+    })
+}
+
+// Issues a self-signed certificate, e.g. for a root CA
+pub fn self_signed_certificate(
+    params: CertificateParams,
+    cpk: &CompactPublicKey,
+    csk: [u8; CSK_BYTES],
+    oid: ObjectIdentifier,
+) -> Result<Certificate, &'static str> {
+    let issuer = params.subject.clone();
+    issue_certificate(params, cpk, &issuer, csk, oid)
+}
+
+// Issues a (non-CA) certificate for the subject and public key of a verified certificate request
+pub fn issue_certificate_for_request(
+    request: &CertReq,
+    serial_number: u64,
+    validity: Validity,
+    issuer: &Name,
+    issuer_csk: [u8; CSK_BYTES],
+    oid: ObjectIdentifier,
+) -> Result<Certificate, &'static str> {
+    let subject_pk = verify_certificate_request(request, oid)?;
+    let params = CertificateParams {
+        serial_number,
+        subject: request.info.subject.clone(),
+        validity,
+        is_ca: false,
+    };
+    issue_certificate(params, &subject_pk, issuer, issuer_csk, oid)
+}
+
+// Checks that `certificate` is signed by `issuer_pk`
+pub fn verify_certificate(
+    certificate: &Certificate,
+    issuer_pk: &CompactPublicKey,
+    oid: ObjectIdentifier,
+) -> Result<(), &'static str> {
+    check_algorithm(&certificate.signature_algorithm, oid)?;
+    if certificate.tbs_certificate.signature != certificate.signature_algorithm {
+        return Err("The signature algorithms of the certificate do not match");
+    }
+
+    let tbs_der = certificate
+        .tbs_certificate
+        .to_der()
+        .map_err(|_| ENCODE_ERROR)?;
+    verify_signature(issuer_pk, &certificate.signature, &tbs_der)
+}
+
+// The MAYO public key of the certificate subject
+pub fn certificate_public_key(
+    certificate: &Certificate,
+    oid: ObjectIdentifier,
+) -> Result<CompactPublicKey, &'static str> {
+    let spki_der = certificate
+        .tbs_certificate
+        .subject_public_key_info
+        .to_der()
+        .map_err(|_| ENCODE_ERROR)?;
+    public_key_from_der(&spki_der, oid)
+}
+
+// Verifies a certificate chain ordered from the leaf to a self-signed root. Every certificate must
+// be issued (by name and signature) by the next one, every issuer must be a CA and all
+// certificates must be valid now. Trust in the root itself is up to the caller.
+pub fn verify_certificate_chain(
+    chain: &[Certificate],
+    oid: ObjectIdentifier,
+) -> Result<(), &'static str> {
+    let root = chain.last().ok_or("The certificate chain is empty")?;
+    let now = SystemTime::now();
+
+    for (i, certificate) in chain.iter().enumerate() {
+        let issuer = chain.get(i + 1).unwrap_or(root);
+        let tbs = &certificate.tbs_certificate;
+
+        if tbs.issuer != issuer.tbs_certificate.subject {
+            return Err("The certificate is not issued by the next certificate in the chain");
+        }
+        if !is_ca(issuer) {
+            return Err("The issuer of a certificate is not a CA");
+        }
+        if now < tbs.validity.not_before.to_system_time()
+            || now > tbs.validity.not_after.to_system_time()
+        {
+            return Err("A certificate in the chain is not valid at the current time");
+        }
+
+        verify_certificate(certificate, &certificate_public_key(issuer, oid)?, oid)?;
+    }
+    Ok(())
+}
+
+// Creates a PKCS#10 certificate request for `subject`, signed with the subject's own key
+pub fn create_certificate_request(
+    subject: Name,
+    cpk: &CompactPublicKey,
+    csk: [u8; CSK_BYTES],
+    oid: ObjectIdentifier,
+) -> Result<CertReq, &'static str> {
+    let info = CertReqInfo {
+        version: x509_cert::request::Version::V1,
+        subject,
+        public_key: subject_public_key_info(cpk, oid)?,
+        attributes: Default::default(),
+    };
+
+    let info_der = info.to_der().map_err(|_| ENCODE_ERROR)?;
+    let signature = sign(csk, &info_der);
+
+    Ok(CertReq {
+        info,
+        algorithm: algorithm_identifier(oid),
+        signature: BitString::from_bytes(&signature).map_err(|_| ENCODE_ERROR)?,
+    })
+}
+
+// Checks the proof of possession of a certificate request and returns the requested public key
+pub fn verify_certificate_request(
+    request: &CertReq,
+    oid: ObjectIdentifier,
+) -> Result<CompactPublicKey, &'static str> {
+    check_algorithm(&request.algorithm, oid)?;
+
+    let spki_der = request.info.public_key.to_der().map_err(|_| ENCODE_ERROR)?;
+    let cpk = public_key_from_der(&spki_der, oid)?;
+
+    let info_der = request.info.to_der().map_err(|_| ENCODE_ERROR)?;
+    verify_signature(&cpk, &request.signature, &info_der)?;
+    Ok(cpk)
+}
+
+const ENCODE_ERROR: &str = "Failed to DER encode the certificate";
+
+fn algorithm_identifier(oid: ObjectIdentifier) -> AlgorithmIdentifierOwned {
+    AlgorithmIdentifierOwned {
+        oid,
+        parameters: None,
+    }
+}
+
+fn subject_public_key_info(
+    cpk: &CompactPublicKey,
+    oid: ObjectIdentifier,
+) -> Result<SubjectPublicKeyInfoOwned, &'static str> {
+    SubjectPublicKeyInfoOwned::from_der(&public_key_to_der(cpk, oid)?).map_err(|_| ENCODE_ERROR)
+}
+
+fn check_algorithm(
+    algorithm: &AlgorithmIdentifierOwned,
+    oid: ObjectIdentifier,
+) -> Result<(), &'static str> {
+    if algorithm.oid != oid || algorithm.parameters.is_some() {
+        return Err("The signature algorithm is not the expected MAYO parameter set");
+    }
+    Ok(())
+}
+
+fn verify_signature(
+    cpk: &CompactPublicKey,
+    signature: &BitString,
+    message: &[u8],
+) -> Result<(), &'static str> {
+    let signature = signature
+        .as_bytes()
+        .filter(|signature| signature.len() == SIG_BYTES)
+        .ok_or("The signature has incorrect length")?;
+
+    if verify(expand_pk(cpk.clone()), signature, message) {
+        Ok(())
+    } else {
+        Err("The signature is invalid")
+    }
+}
+
+fn is_ca(certificate: &Certificate) -> bool {
+    matches!(
+        certificate.tbs_certificate.get::<BasicConstraints>(),
+        Ok(Some((_, BasicConstraints { ca: true, .. })))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key_encoding::DEFAULT_OID;
+    use crate::mayo_functionality::compact_key_gen;
+    use std::str::FromStr;
+    use std::time::Duration;
+    use x509_cert::der::{DecodePem, EncodePem};
+
+    fn validity() -> Validity {
+        Validity::from_now(Duration::from_secs(3600)).unwrap()
+    }
+
+    fn root_certificate(name: &str) -> (Certificate, [u8; CSK_BYTES]) {
+        let (cpk, csk) = compact_key_gen();
+        let params = CertificateParams {
+            serial_number: 1,
+            subject: Name::from_str(name).unwrap(),
+            validity: validity(),
+            is_ca: true,
+        };
+        (
+            self_signed_certificate(params, &cpk, csk, DEFAULT_OID).unwrap(),
+            csk,
+        )
+    }
+
+    #[test]
+    fn test_root_to_leaf_chain() {
+        let (root, root_csk) = root_certificate("CN=MAYO Root,O=Example");
+
+        // The leaf requests a certificate from the root
+        let (leaf_cpk, leaf_csk) = compact_key_gen();
+        let request = create_certificate_request(
+            Name::from_str("CN=leaf.example.com").unwrap(),
+            &leaf_cpk,
+            leaf_csk,
+            DEFAULT_OID,
+        )
+        .unwrap();
+        assert!(verify_certificate_request(&request, DEFAULT_OID).unwrap() == leaf_cpk);
+
+        let leaf = issue_certificate_for_request(
+            &request,
+            2,
+            validity(),
+            &root.tbs_certificate.subject,
+            root_csk,
+            DEFAULT_OID,
+        )
+        .unwrap();
+
+        assert!(certificate_public_key(&leaf, DEFAULT_OID).unwrap() == leaf_cpk);
+        assert!(verify_certificate_chain(&[leaf.clone(), root.clone()], DEFAULT_OID).is_ok());
+        assert!(verify_certificate_chain(std::slice::from_ref(&root), DEFAULT_OID).is_ok());
+
+        // Wrong order, and a leaf is not a CA
+        assert!(verify_certificate_chain(&[root.clone(), leaf.clone()], DEFAULT_OID).is_err());
+
+        // PEM round trip keeps the chain valid
+        let pem = leaf.to_pem(Default::default()).unwrap();
+        let decoded = Certificate::from_pem(&pem).unwrap();
+        assert!(verify_certificate_chain(&[decoded, root.clone()], DEFAULT_OID).is_ok());
+
+        // The leaf does not verify under another root with the same name
+        let (other_root, _) = root_certificate("CN=MAYO Root,O=Example");
+        assert!(verify_certificate_chain(&[leaf, other_root], DEFAULT_OID).is_err());
+    }
+
+    #[test]
+    fn test_tampered_certificate_and_request() {
+        let (root, _) = root_certificate("CN=MAYO Root");
+
+        let mut tampered = root.clone();
+        tampered.tbs_certificate.serial_number = SerialNumber::from(3u64);
+        assert!(verify_certificate_chain(&[tampered], DEFAULT_OID).is_err());
+
+        let other_oid = ObjectIdentifier::new_unwrap("1.3.6.1.4.1.99999.1");
+        assert!(verify_certificate_chain(&[root], other_oid).is_err());
+
+        let (cpk, csk) = compact_key_gen();
+        let mut request =
+            create_certificate_request(Name::from_str("CN=leaf").unwrap(), &cpk, csk, DEFAULT_OID)
+                .unwrap();
+        request.info.subject = Name::from_str("CN=someone else").unwrap();
+        assert!(verify_certificate_request(&request, DEFAULT_OID).is_err());
+
+        let request_der = request.to_der().unwrap();
+        assert!(CertReq::from_der(&request_der).is_ok());
+    }
+}
@@ -0,0 +1,245 @@
+// ACVP-style JSON sample vectors (feature `acvp`, see lib::acvp) for every parameter set in
+// tests/acvp/MAYO_n/{keyGen,sigGen,sigVer}/, each with a prompt.json and expectedResults.json:
+//
+//   cargo test --test acvp --no-default-features --features mayo1,acvp
+//
+// The samples are generated from the first 10 records of the NIST KAT files by the ignored test
+// below. The KATs do not cover the second test groups of sigGen and sigVer (deterministic
+// signatures with a context), their expected results come from this implementation.
+#![cfg(feature = "acvp")]
+
+use std::fs;
+use std::path::PathBuf;
+
+use serde_json::{json, Value};
+
+use lib::acvp::{compare, parse_acvp, respond, ACVP_ALGORITHM};
+use lib::constants::{R_BYTES, SIG_BYTES, SK_SEED_BYTES, VERSION};
+use lib::crypto_primitives::{safe_random_bytes, safe_random_bytes_init};
+use lib::kat::{kat_response_path, read_kat_file, KAT_SEED_BYTES};
+use lib::utils::bytes_to_hex_string;
+
+const MODES: [&str; 3] = ["keyGen", "sigGen", "sigVer"];
+
+// KAT records the samples are generated from
+const SAMPLE_TESTS: usize = 10;
+
+// Records of the deterministic test groups with a context
+const CONTEXT_TESTS: usize = 4;
+
+const CONTEXT: &[u8] = b"ACVP sample context";
+
+fn sample_path(mode: &str, file: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/acvp")
+        .join(VERSION)
+        .join(mode)
+        .join(file)
+}
+
+fn read_sample(mode: &str, file: &str) -> Value {
+    let path = sample_path(mode, file);
+    let contents =
+        fs::read_to_string(&path).unwrap_or_else(|error| panic!("{}: {}", path.display(), error));
+    parse_acvp(&contents).unwrap_or_else(|error| panic!("{}: {}", path.display(), error))
+}
+
+#[test]
+fn test_acvp_samples() {
+    for mode in MODES {
+        let prompt = read_sample(mode, "prompt.json");
+        let expected = read_sample(mode, "expectedResults.json");
+        let response = respond(&prompt).unwrap_or_else(|error| panic!("{}: {}", mode, error));
+
+        let mismatches = compare(&expected, &response).unwrap();
+        assert!(
+            mismatches.is_empty(),
+            "{} results differ:\n{}",
+            mode,
+            mismatches
+                .iter()
+                .map(|mismatch| mismatch.to_string())
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+    }
+}
+
+#[test]
+fn test_expected_results_cover_prompts() {
+    for mode in MODES {
+        let prompt = read_sample(mode, "prompt.json");
+        let expected = read_sample(mode, "expectedResults.json");
+        assert_eq!(prompt["vsId"], expected["vsId"]);
+
+        let test_count = |vector_set: &Value| -> usize {
+            vector_set["testGroups"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|group| group["tests"].as_array().unwrap().len())
+                .sum()
+        };
+        assert!(test_count(&prompt) >= SAMPLE_TESTS);
+        assert_eq!(test_count(&prompt), test_count(&expected));
+    }
+}
+
+fn hex(bytes: &[u8]) -> Value {
+    Value::String(bytes_to_hex_string(bytes, false))
+}
+
+fn vector_set(vs_id: u64, mode: &str, groups: Vec<Value>) -> Value {
+    json!({
+        "vsId": vs_id,
+        "algorithm": ACVP_ALGORITHM,
+        "mode": mode,
+        "revision": "1.0",
+        "isSample": true,
+        "testGroups": groups,
+    })
+}
+
+fn write_sample(mode: &str, prompt: Value, expected: Value) {
+    for (file, value) in [("prompt.json", prompt), ("expectedResults.json", expected)] {
+        let path = sample_path(mode, file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let json = serde_json::to_string_pretty(&value).unwrap() + "\n";
+        fs::write(path, json).unwrap();
+    }
+}
+
+// The first result of a response with a single test
+fn single_result(prompt: Value) -> Value {
+    respond(&prompt).unwrap()["testGroups"][0]["tests"][0].clone()
+}
+
+// Writes the samples of the compiled parameter set:
+//   cargo test --test acvp --no-default-features --features mayoN,acvp -- --ignored
+#[test]
+#[ignore]
+fn generate_acvp_samples() {
+    let path = kat_response_path(VERSION).expect("No KAT file for the parameter set");
+    let kat = read_kat_file(path).unwrap();
+
+    let group = |tg_id: u64, tests: Vec<Value>| {
+        json!({
+            "tgId": tg_id,
+            "testType": "AFT",
+            "parameterSet": VERSION,
+            "tests": tests,
+        })
+    };
+    let results = |tg_id: u64, tests: Vec<Value>| json!({"tgId": tg_id, "tests": tests});
+
+    let (mut key_gen, mut key_gen_results) = (Vec::new(), Vec::new());
+    let (mut sig_gen, mut sig_gen_results) = (Vec::new(), Vec::new());
+    let (mut sig_ver, mut sig_ver_results) = (Vec::new(), Vec::new());
+    let (mut context_sig_gen, mut context_sig_gen_results) = (Vec::new(), Vec::new());
+    let (mut context_sig_ver, mut context_sig_ver_results) = (Vec::new(), Vec::new());
+
+    for (i, record) in kat.records.iter().take(SAMPLE_TESTS).enumerate() {
+        let tc_id = i as u64 + 1;
+        let pk = record.pk.as_deref().unwrap();
+        let sk = record.sk.as_deref().unwrap();
+        let signature = &record.sm.as_deref().unwrap()[..SIG_BYTES];
+
+        // The randomness keygen and sign draw for the record
+        let mut seed = record.seed;
+        safe_random_bytes_init(&mut seed, &[0u8; KAT_SEED_BYTES], 256);
+        let mut sk_seed = [0u8; SK_SEED_BYTES];
+        safe_random_bytes(&mut sk_seed, SK_SEED_BYTES as u64);
+        let mut rnd = [0u8; R_BYTES];
+        safe_random_bytes(&mut rnd, R_BYTES as u64);
+        assert_eq!(sk_seed, sk);
+
+        key_gen.push(json!({"tcId": tc_id, "seed": hex(&sk_seed)}));
+        key_gen_results.push(json!({"tcId": tc_id, "pk": hex(pk), "sk": hex(sk)}));
+
+        let message = hex(&record.msg);
+        sig_gen.push(json!({"tcId": tc_id, "sk": hex(sk), "message": message, "rnd": hex(&rnd)}));
+        sig_gen_results.push(json!({"tcId": tc_id, "signature": hex(signature)}));
+
+        // Every third signature is modified and every third message, the rest verifies
+        let mut modified_signature = signature.to_vec();
+        let mut modified_message = record.msg.clone();
+        match i % 3 {
+            1 => modified_signature[i] ^= 1,
+            2 => modified_message[i] ^= 1,
+            _ => {}
+        }
+        let (message, signature) = (hex(&modified_message), hex(&modified_signature));
+        sig_ver.push(
+            json!({"tcId": tc_id, "pk": hex(pk), "message": message, "signature": signature}),
+        );
+        sig_ver_results.push(json!({"tcId": tc_id, "testPassed": i % 3 == 0}));
+
+        // Deterministic signatures with a context, verified under the right and a wrong context
+        if i >= CONTEXT_TESTS {
+            continue;
+        }
+        let tc_id = SAMPLE_TESTS as u64 + tc_id;
+        let message = hex(&record.msg);
+        let context_test =
+            json!({"tcId": tc_id, "sk": hex(sk), "message": message, "context": hex(CONTEXT)});
+        let mut prompt = vector_set(2, "sigGen", vec![group(2, vec![context_test.clone()])]);
+        prompt["testGroups"][0]["deterministic"] = json!(true);
+        let result = single_result(prompt);
+        context_sig_gen.push(context_test);
+        context_sig_gen_results.push(result.clone());
+
+        let context = hex(if i % 2 == 0 {
+            CONTEXT
+        } else {
+            b"Another context"
+        });
+        let signature = &result["signature"];
+        context_sig_ver.push(json!({
+            "tcId": tc_id,
+            "pk": hex(pk),
+            "message": hex(&record.msg),
+            "signature": signature,
+            "context": context,
+        }));
+        context_sig_ver_results.push(json!({"tcId": tc_id, "testPassed": i % 2 == 0}));
+    }
+
+    let mut deterministic_group = group(2, context_sig_gen);
+    deterministic_group["deterministic"] = json!(true);
+    let mut randomized_group = group(1, sig_gen);
+    randomized_group["deterministic"] = json!(false);
+
+    write_sample(
+        "keyGen",
+        vector_set(1, "keyGen", vec![group(1, key_gen)]),
+        vector_set(1, "keyGen", vec![results(1, key_gen_results)]),
+    );
+    write_sample(
+        "sigGen",
+        vector_set(2, "sigGen", vec![randomized_group, deterministic_group]),
+        vector_set(
+            2,
+            "sigGen",
+            vec![
+                results(1, sig_gen_results),
+                results(2, context_sig_gen_results),
+            ],
+        ),
+    );
+    write_sample(
+        "sigVer",
+        vector_set(
+            3,
+            "sigVer",
+            vec![group(1, sig_ver), group(2, context_sig_ver)],
+        ),
+        vector_set(
+            3,
+            "sigVer",
+            vec![
+                results(1, sig_ver_results),
+                results(2, context_sig_ver_results),
+            ],
+        ),
+    );
+}
@@ -0,0 +1,109 @@
+// End-to-end test of the command line interface (see lib::cli).
+//
+// keygen and sign reseed the global randomness source from the operating system, so this runs in
+// its own test binary instead of next to the lib unit tests that expect a fixed seed.
+//
+//   cargo test --test cli --no-default-features --features mayo1,cli
+#![cfg(feature = "cli")]
+
+use std::fs;
+
+use lib::cli::{decode, run, EXIT_INVALID_SIGNATURE, EXIT_IO, EXIT_MALFORMED, EXIT_OK, EXIT_USAGE};
+use lib::constants::{CPK_BYTES, VERSION};
+
+fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+#[test]
+fn test_keygen_sign_verify() {
+    let dir = std::env::temp_dir().join(format!("mayo_cli_test_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = |name: &str| dir.join(name).to_str().unwrap().to_string();
+
+    fs::write(path("message"), b"artifact").unwrap();
+    fs::write(path("other"), b"other artifact").unwrap();
+
+    let (sk, pk, sig, derived_pk) = (path("key.sk"), path("key.pk"), path("sig"), path("pk2"));
+
+    // An existing, world readable secret key file is restricted when it is overwritten
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::write(&sk, b"old key").unwrap();
+        fs::set_permissions(&sk, fs::Permissions::from_mode(0o644)).unwrap();
+    }
+
+    let level = VERSION.trim_start_matches("MAYO_");
+    let key = path("key");
+    assert_eq!(
+        run(&args(&["keygen", "--level", level, "--out", &key])),
+        EXIT_OK
+    );
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(&sk).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    let message = path("message");
+    assert_eq!(
+        run(&args(&[
+            "sign", "--key", &sk, "--format", "base64", "--out", &sig, &message
+        ])),
+        EXIT_OK
+    );
+    assert_eq!(
+        run(&args(&["verify", "--pk", &pk, "--sig", &sig, &message])),
+        EXIT_OK
+    );
+    assert_eq!(
+        run(&args(&[
+            "verify",
+            "--pk",
+            &pk,
+            "--sig",
+            &sig,
+            &path("other")
+        ])),
+        EXIT_INVALID_SIGNATURE
+    );
+
+    // The public key derived from the secret key matches the generated one
+    assert_eq!(
+        run(&args(&[
+            "pubkey",
+            "--format",
+            "raw",
+            "--out",
+            &derived_pk,
+            &sk
+        ])),
+        EXIT_OK
+    );
+    assert_eq!(
+        decode(&fs::read(&derived_pk).unwrap(), CPK_BYTES),
+        decode(&fs::read(&pk).unwrap(), CPK_BYTES)
+    );
+
+    // Malformed inputs and bad arguments
+    assert_eq!(
+        run(&args(&[
+            "verify", "--pk", &message, "--sig", &sig, &message
+        ])),
+        EXIT_MALFORMED
+    );
+    assert_eq!(
+        run(&args(&["sign", "--key", &path("missing"), &message])),
+        EXIT_IO
+    );
+    assert_eq!(
+        run(&args(&["keygen", "--level", "4", "--out", &key])),
+        EXIT_USAGE
+    );
+    assert_eq!(run(&args(&["frobnicate"])), EXIT_USAGE);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,27 @@
+// Reproduces the NIST KAT file through the C interface. The KAT generator in
+// src/genkat/PQCgenKAT_sign_ffi.c is compiled by build.rs and calls the exported crypto_sign
+// functions. This lives in its own test binary since it reseeds the global randomness source.
+// The generator uses the C randomness source, so it is not built with the portable backend.
+#![cfg(not(mayo_portable))]
+
+use std::ffi::CString;
+use std::os::raw::{c_char, c_int};
+
+use lib::constants::COMPARE_FILE_NAME;
+use lib::write_and_compare_kat_file::compare_files;
+
+extern "C" {
+    fn mayo_ffi_genkat(path: *const c_char) -> c_int;
+}
+
+#[test]
+fn test_kat_through_c_interface() {
+    let output = std::env::temp_dir().join(format!("mayo_ffi_kat_{}.rsp", std::process::id()));
+    let output_path = output.to_str().unwrap();
+    let c_path = CString::new(output_path).unwrap();
+
+    assert_eq!(unsafe { mayo_ffi_genkat(c_path.as_ptr()) }, 0);
+    assert!(compare_files(output_path, COMPARE_FILE_NAME));
+
+    std::fs::remove_file(output).unwrap();
+}
@@ -0,0 +1,123 @@
+// Known answer tests of the compiled parameter set against the NIST KAT files bundled in
+// src/genkat/Results MAYO, checked record by record (see lib::kat):
+//
+//   cargo test --test kat --no-default-features --features mayo2
+//
+// This lives in its own test binary since it reseeds the global randomness source, and the tests
+// in it are serialized for the same reason.
+use std::sync::Mutex;
+
+use lib::constants::VERSION;
+use lib::kat::{
+    check_kat_file, check_kat_inputs, check_kat_record, kat_request_path, kat_response_path,
+    read_kat_file, KatMismatch, KAT_VERSIONS,
+};
+
+static RANDOMNESS: Mutex<()> = Mutex::new(());
+
+// Most mismatches to print, a broken implementation usually fails every record
+const MAX_REPORTED: usize = 20;
+
+fn check_kat(version: &str) {
+    let _guard = RANDOMNESS.lock().unwrap();
+    assert_eq!(VERSION, version);
+
+    let path = kat_response_path(version).expect("No KAT file for the parameter set");
+    let file = read_kat_file(&path).unwrap_or_else(|error| panic!("{}: {}", path.display(), error));
+    assert_eq!(file.records.len(), 100);
+
+    let mut mismatches = check_kat_inputs(&file);
+    mismatches.extend(check_kat_file(&file));
+    assert!(
+        mismatches.is_empty(),
+        "{} fields of {} differ:\n{}",
+        mismatches.len(),
+        path.display(),
+        report(&mismatches)
+    );
+}
+
+fn report(mismatches: &[KatMismatch]) -> String {
+    let mut lines: Vec<String> = mismatches
+        .iter()
+        .take(MAX_REPORTED)
+        .map(|mismatch| mismatch.to_string())
+        .collect();
+    if mismatches.len() > MAX_REPORTED {
+        lines.push(format!("... and {} more", mismatches.len() - MAX_REPORTED));
+    }
+    lines.join("\n")
+}
+
+#[cfg(feature = "mayo1")]
+#[test]
+fn kat_mayo1() {
+    check_kat("MAYO_1");
+}
+
+#[cfg(feature = "mayo2")]
+#[test]
+fn kat_mayo2() {
+    check_kat("MAYO_2");
+}
+
+#[cfg(feature = "mayo3")]
+#[test]
+fn kat_mayo3() {
+    check_kat("MAYO_3");
+}
+
+#[cfg(feature = "mayo5")]
+#[test]
+fn kat_mayo5() {
+    check_kat("MAYO_5");
+}
+
+#[test]
+fn test_request_file() {
+    let _guard = RANDOMNESS.lock().unwrap();
+    let file = read_kat_file(kat_request_path(VERSION).unwrap()).unwrap();
+    assert_eq!(check_kat_inputs(&file), vec![]);
+
+    // Without expected results only the signature verification is checked
+    for record in file.records.iter().take(10) {
+        assert_eq!(check_kat_record(record), vec![]);
+    }
+}
+
+#[test]
+fn test_reports_mismatching_fields() {
+    let _guard = RANDOMNESS.lock().unwrap();
+    let file = read_kat_file(kat_response_path(VERSION).unwrap()).unwrap();
+
+    let mut record = file.records[3].clone();
+    record.sm.as_mut().unwrap()[10] ^= 1;
+    record.smlen = Some(record.smlen.unwrap() + 1);
+    let mismatches = check_kat_record(&record);
+
+    let fields: Vec<_> = mismatches
+        .iter()
+        .map(|mismatch| (mismatch.count, mismatch.field))
+        .collect();
+    assert_eq!(fields, vec![(Some(3), "sm"), (Some(3), "smlen")]);
+    assert!(mismatches[0].expected.contains("at byte 10"));
+
+    let mut tampered = file.clone();
+    tampered.records[5].seed[0] ^= 1;
+    tampered.records[7].msg[40] ^= 1;
+    let fields: Vec<_> = check_kat_inputs(&tampered)
+        .iter()
+        .map(|mismatch| (mismatch.count, mismatch.field))
+        .collect();
+    assert_eq!(fields, vec![(Some(5), "seed"), (Some(7), "msg")]);
+
+    // The KAT file of another parameter set is rejected as a whole
+    let other = KAT_VERSIONS
+        .iter()
+        .find(|version| **version != VERSION)
+        .unwrap();
+    let other_file = read_kat_file(kat_response_path(other).unwrap()).unwrap();
+    let mismatches = check_kat_file(&other_file);
+    assert_eq!(mismatches.len(), 1);
+    assert_eq!(mismatches[0].field, "algorithm");
+}
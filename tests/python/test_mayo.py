@@ -0,0 +1,93 @@
+# Tests of the Python bindings (src/python.rs). Build the module into the active environment and
+# run with a local interpreter:
+#
+#   maturin develop --release --no-default-features --features python,mayo2,portable
+#   python -m unittest discover tests/python
+#
+# `portable` builds the Rust primitives instead of the ARMv8 C sources, leave it out on ARMv8.
+
+import unittest
+
+import mayo
+
+
+class TestParameters(unittest.TestCase):
+    def test_constants(self):
+        self.assertIn(mayo.VERSION, ("MAYO_1", "MAYO_2", "MAYO_3", "MAYO_5"))
+        self.assertEqual(mayo.Q, 16)
+        self.assertEqual(mayo.CSK_BYTES, mayo.SK_SEED_BYTES)
+        self.assertTrue(issubclass(mayo.MayoError, ValueError))
+
+
+class TestSignatures(unittest.TestCase):
+    def setUp(self):
+        self.pk, self.sk = mayo.keygen()
+
+    def test_keygen(self):
+        self.assertIsInstance(self.pk, bytes)
+        self.assertEqual(len(self.pk), mayo.CPK_BYTES)
+        self.assertEqual(len(self.sk), mayo.CSK_BYTES)
+        self.assertEqual(mayo.public_key(self.sk), self.pk)
+        self.assertEqual(mayo.keygen_from_seed(self.sk), (self.pk, self.sk))
+
+    def test_sign_verify(self):
+        signature = mayo.sign(self.sk, b"message")
+        self.assertEqual(len(signature), mayo.SIG_BYTES)
+        self.assertTrue(mayo.verify(self.pk, b"message", signature))
+        self.assertFalse(mayo.verify(self.pk, b"other message", signature))
+        self.assertFalse(mayo.verify(self.pk, b"message", signature[:-1]))
+
+        tampered = bytes([signature[0] ^ 1]) + signature[1:]
+        self.assertFalse(mayo.verify(self.pk, b"message", tampered))
+
+        other_pk, _ = mayo.keygen()
+        self.assertFalse(mayo.verify(other_pk, b"message", signature))
+
+    def test_context(self):
+        signature = mayo.sign(self.sk, b"message", context=b"context")
+        self.assertTrue(mayo.verify(self.pk, b"message", signature, context=b"context"))
+        self.assertFalse(mayo.verify(self.pk, b"message", signature))
+
+        with self.assertRaises(mayo.MayoError):
+            mayo.sign(self.sk, b"message", context=bytes(256))
+
+    def test_seed_randomness(self):
+        mayo.seed_randomness(bytes(range(48)))
+        first = mayo.keygen()
+        mayo.seed_randomness(bytes(range(48)))
+        self.assertEqual(mayo.keygen(), first)
+
+        with self.assertRaises(mayo.MayoError):
+            mayo.seed_randomness(bytes(47))
+
+    def test_malformed_input(self):
+        with self.assertRaises(mayo.MayoError):
+            mayo.sign(self.sk[:-1], b"message")
+        with self.assertRaises(mayo.MayoError):
+            mayo.verify(self.pk[:-1], b"message", bytes(mayo.SIG_BYTES))
+        with self.assertRaises(TypeError):
+            mayo.sign("not bytes", b"message")
+
+
+class TestKeyEncoding(unittest.TestCase):
+    def test_round_trip(self):
+        pk, sk = mayo.keygen()
+
+        self.assertEqual(mayo.public_key_from_der(mayo.public_key_to_der(pk)), pk)
+        self.assertEqual(mayo.secret_key_from_der(mayo.secret_key_to_der(sk)), sk)
+
+        pem = mayo.public_key_to_pem(pk)
+        self.assertTrue(pem.startswith("-----BEGIN PUBLIC KEY-----"))
+        self.assertEqual(mayo.public_key_from_pem(pem), pk)
+        self.assertEqual(mayo.secret_key_from_pem(mayo.secret_key_to_pem(sk)), sk)
+
+    def test_invalid_encoding(self):
+        pk, sk = mayo.keygen()
+        with self.assertRaises(mayo.MayoError):
+            mayo.public_key_from_der(b"\x30\x00")
+        with self.assertRaises(mayo.MayoError):
+            mayo.public_key_from_pem(mayo.secret_key_to_pem(sk))
+
+
+if __name__ == "__main__":
+    unittest.main()
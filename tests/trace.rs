@@ -0,0 +1,490 @@
+// Intermediate-value test vectors (feature `trace`): the named intermediates of keygen, sign and
+// verify for the first NIST KAT record of every parameter set, stored in tests/vectors/ in the
+// format of lib::trace, which encodes them as the MAYO specification and MAYO-C do. All values,
+// including P1 and L, are stored in full so they can be compared element by element.
+//
+//   cargo test --test trace --no-default-features --features mayo1,trace
+//
+// The vectors are checked three ways: the implementation reproduces them, the outputs of the
+// record (pk and signature) match the NIST KAT, and every intermediate is recomputed from the
+// seeds by the independent reference below, which follows the specification on unpacked elements.
+// A port that diverges can diff its own dump against the vectors to find the first step that
+// differs, e.g. whether expand_sk, M_i or the reduction of A and y is wrong.
+#![cfg(feature = "trace")]
+
+use std::fs;
+use std::path::PathBuf;
+
+use lib::constants::{CPK_BYTES, CSK_BYTES, R_BYTES, SK_SEED_BYTES, VERSION};
+use lib::crypto_primitives::{safe_random_bytes, safe_random_bytes_init};
+use lib::kat::{kat_response_path, read_kat_file, KatRecord, KAT_SEED_BYTES};
+use lib::mayo_functionality::{
+    api_sign_open, compact_key_gen_from_seed, sign_digest_with_randomness,
+};
+use lib::message_hasher::MessageHasher;
+use lib::trace::{capture, record, Trace};
+
+fn vector_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/vectors")
+        .join(format!("trace_{}.txt", VERSION))
+}
+
+fn first_kat_record() -> KatRecord {
+    let path = kat_response_path(VERSION).expect("No KAT file for the parameter set");
+    let mut file = read_kat_file(path).unwrap();
+    file.records.swap_remove(0)
+}
+
+// Key generation, signing and verification of `msg` with the trace of all three
+struct Traced {
+    public_key: Vec<u8>,
+    signature: Vec<u8>,
+    valid: bool,
+    trace: Trace,
+}
+
+fn traced_operations(sk_seed: &[u8], msg: &[u8], r: &[u8]) -> Traced {
+    let sk_seed: [u8; SK_SEED_BYTES] = sk_seed.try_into().expect("sk_seed has incorrect length");
+    let r: [u8; R_BYTES] = r.try_into().expect("r has incorrect length");
+
+    let ((public_key, signature, valid), trace) = capture(|| {
+        record("sk_seed", sk_seed.to_vec());
+        record("msg", msg.to_vec());
+        record("r", r.to_vec());
+
+        let (cpk, csk) = compact_key_gen_from_seed(sk_seed);
+        let mut hasher = MessageHasher::new();
+        hasher.update(msg);
+        let signature = sign_digest_with_randomness(csk, &hasher.finalize(), r).to_vec();
+        let (valid, _) = api_sign_open([&signature[..], msg].concat(), cpk.clone());
+        (cpk.to_bytes().to_vec(), signature, valid)
+    });
+
+    Traced {
+        public_key,
+        signature,
+        valid,
+        trace,
+    }
+}
+
+// Independent reference for the vectors: recomputes every traced value from sk_seed, msg and r as
+// in the MAYO specification, on unpacked elements (Gf16, ExtensionElement and the echelon form of
+// lib::linear_algebra). Only SHAKE256 and AES-128-CTR are shared with the implementation, the
+// decoding of the bitsliced P1 and P2, the matrix products, the reduction by f(z) and the solving
+// of the linear system are written out here.
+mod reference {
+    use lib::constants::{
+        DIGEST_BYTES, K, M, N, O, O_BYTES, P1_BYTES, P2_BYTES, PK_SEED_BYTES, SALT_BYTES, V,
+        V_BYTES,
+    };
+    use lib::crypto_primitives::{safe_aes_128_ctr, safe_shake256};
+    use lib::extension_field::ExtensionElement;
+    use lib::finite_field::Gf16;
+    use lib::linear_algebra::DynMatrix;
+    use lib::matrix::Vector;
+    use lib::trace::Trace;
+
+    // A matrix row by row
+    type Mat = Vec<Vec<Gf16>>;
+
+    fn shake(input: &[u8], len: usize) -> Vec<u8> {
+        let mut output = vec![0u8; len];
+        safe_shake256(&mut output, len as u64, input, input.len() as u64);
+        output
+    }
+
+    fn aes_ctr(seed: &[u8], len: usize) -> Vec<u8> {
+        let mut words = vec![0u32; len.div_ceil(4)];
+        safe_aes_128_ctr(&mut words, len as u64, seed);
+        let bytes: Vec<u8> = words.iter().flat_map(|word| word.to_le_bytes()).collect();
+        bytes[..len].to_vec()
+    }
+
+    // Decode: two elements per byte, first element in the low nibble
+    fn decode(bytes: &[u8], count: usize) -> Vec<Gf16> {
+        (0..count)
+            .map(|i| Gf16::from_nibble((bytes[i / 2] >> (4 * (i % 2))) & 0xF))
+            .collect()
+    }
+
+    fn encode(elements: &[Gf16]) -> Vec<u8> {
+        elements
+            .chunks(2)
+            .map(|pair| pair[0].value() | pair.get(1).map_or(0, |second| second.value() << 4))
+            .collect()
+    }
+
+    // The bitsliced m-vector of M / 2 bytes: bit b of element i is bit i % 8 of byte
+    // b * M / 8 + i / 8
+    fn decode_bitsliced(bytes: &[u8]) -> Vec<Gf16> {
+        (0..M)
+            .map(|i| {
+                let bits = (0..4).map(|b| ((bytes[b * M / 8 + i / 8] >> (i % 8)) & 1) << b);
+                Gf16::from_nibble(bits.sum())
+            })
+            .collect()
+    }
+
+    // The m matrices of size rows x cols stored as bitsliced m-vectors, entry by entry row by
+    // row, only the upper triangle if `triangular`
+    fn decode_matrices(bytes: &[u8], rows: usize, cols: usize, triangular: bool) -> Vec<Mat> {
+        let mut matrices = vec![vec![vec![Gf16::ZERO; cols]; rows]; M];
+        let mut m_vectors = bytes.chunks(M / 2);
+        for i in 0..rows {
+            for j in (if triangular { i } else { 0 })..cols {
+                let m_vector = decode_bitsliced(m_vectors.next().unwrap());
+                for (matrix, element) in matrices.iter_mut().zip(m_vector) {
+                    matrix[i][j] = element;
+                }
+            }
+        }
+        matrices
+    }
+
+    // The m matrices as their m-vectors entry by entry, in the encoding of lib::trace
+    fn encode_matrices(matrices: &[Mat], triangular: bool) -> Vec<u8> {
+        let (rows, cols) = (matrices[0].len(), matrices[0][0].len());
+        let mut bytes = Vec::new();
+        for i in 0..rows {
+            for j in (if triangular { i } else { 0 })..cols {
+                let m_vector: Vec<Gf16> = matrices.iter().map(|matrix| matrix[i][j]).collect();
+                bytes.extend(encode(&m_vector));
+            }
+        }
+        bytes
+    }
+
+    fn mul(a: &Mat, b: &Mat) -> Mat {
+        a.iter()
+            .map(|row| {
+                (0..b[0].len())
+                    .map(|j| row.iter().zip(b).map(|(x, b_row)| *x * b_row[j]).sum())
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn add(a: &Mat, b: &Mat) -> Mat {
+        a.iter()
+            .zip(b)
+            .map(|(a_row, b_row)| a_row.iter().zip(b_row).map(|(x, y)| *x + *y).collect())
+            .collect()
+    }
+
+    fn transpose(a: &Mat) -> Mat {
+        (0..a[0].len())
+            .map(|j| a.iter().map(|row| row[j]).collect())
+            .collect()
+    }
+
+    // Upper(A): the entries below the diagonal added onto the ones above it
+    fn upper(a: &Mat) -> Mat {
+        let n = a.len();
+        (0..n)
+            .map(|i| {
+                (0..n)
+                    .map(|j| match j.cmp(&i) {
+                        std::cmp::Ordering::Less => Gf16::ZERO,
+                        std::cmp::Ordering::Equal => a[i][i],
+                        std::cmp::Ordering::Greater => a[i][j] + a[j][i],
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn mul_vec(a: &Mat, x: &[Gf16]) -> Vec<Gf16> {
+        a.iter()
+            .map(|row| row.iter().zip(x).map(|(a, x)| *a * *x).sum())
+            .collect()
+    }
+
+    fn dot(x: &[Gf16], y: &[Gf16]) -> Gf16 {
+        x.iter().zip(y).map(|(x, y)| *x * *y).sum()
+    }
+
+    fn element(coefficients: Vec<Gf16>) -> ExtensionElement {
+        ExtensionElement(Vector(coefficients.try_into().unwrap()))
+    }
+
+    // sum over the pairs i <= j (j from K - 1 down to i, ell counting up) of z^ell * u_ij with
+    // u_ii = x_i^T P_a x_i and u_ij = x_i^T P_a x_j + x_j^T P_a x_i for every matrix P_a
+    fn whipped_sum(matrices: &[Mat], x: &[Vec<Gf16>]) -> ExtensionElement {
+        // P_a x_j for all a and j
+        let products: Vec<Vec<Vec<Gf16>>> = matrices
+            .iter()
+            .map(|p| x.iter().map(|x_j| mul_vec(p, x_j)).collect())
+            .collect();
+
+        let mut sum = ExtensionElement::zero();
+        let mut ell = 0;
+        for i in 0..K {
+            for j in (i..K).rev() {
+                let u: Vec<Gf16> = products
+                    .iter()
+                    .map(|p_x| {
+                        if i == j {
+                            dot(&x[i], &p_x[i])
+                        } else {
+                            dot(&x[i], &p_x[j]) + dot(&x[j], &p_x[i])
+                        }
+                    })
+                    .collect();
+                sum += element(u).mul_by_z_pow(ell);
+                ell += 1;
+            }
+        }
+        sum
+    }
+
+    pub fn trace(sk_seed: &[u8], msg: &[u8], r: &[u8]) -> Trace {
+        let mut trace = Trace::default();
+        let mut record = |name: &str, value: Vec<u8>| trace.entries.push((name.to_string(), value));
+        record("sk_seed", sk_seed.to_vec());
+        record("msg", msg.to_vec());
+        record("r", r.to_vec());
+
+        // Keygen (Algorithm 5) and expand_sk (Algorithm 6)
+        let s = shake(sk_seed, PK_SEED_BYTES + O_BYTES);
+        let (pk_seed, o_bytes) = s.split_at(PK_SEED_BYTES);
+        let o: Mat = decode(o_bytes, V * O).chunks(O).map(<[Gf16]>::to_vec).collect();
+
+        let p = aes_ctr(pk_seed, P1_BYTES + P2_BYTES);
+        let p1 = decode_matrices(&p[..P1_BYTES], V, V, true);
+        let p2 = decode_matrices(&p[P1_BYTES..], V, O, false);
+        let p3: Vec<Mat> = p1
+            .iter()
+            .zip(&p2)
+            .map(|(p1, p2)| upper(&mul(&transpose(&o), &add(&mul(p1, &o), p2))))
+            .collect();
+        let l: Vec<Mat> = p1
+            .iter()
+            .zip(&p2)
+            .map(|(p1, p2)| add(&mul(&add(p1, &transpose(p1)), &o), p2))
+            .collect();
+
+        record("pk_seed", pk_seed.to_vec());
+        record("O", o_bytes.to_vec());
+        record("P3", encode_matrices(&p3, true));
+        record("O", o_bytes.to_vec());
+        record("P1", encode_matrices(&p1, true));
+        record("L", encode_matrices(&l, false));
+
+        // Sign (Algorithm 8)
+        let digest = shake(msg, DIGEST_BYTES);
+        let salt = shake(&[&digest, r, sk_seed].concat(), SALT_BYTES);
+        let t = decode(&shake(&[&digest[..], &salt].concat(), M / 2), M);
+        record("salt", salt.clone());
+        record("t", encode(&t));
+
+        let mut solution = None;
+        for ctr in 0..=255u8 {
+            record("ctr", vec![ctr]);
+            let input = [&digest[..], &salt, sk_seed, &[ctr]].concat();
+            let bytes = shake(&input, K * V_BYTES + K * O / 2);
+            let v: Vec<Vec<Gf16>> = (0..K)
+                .map(|i| decode(&bytes[i * V_BYTES..], V))
+                .collect();
+            let random = decode(&bytes[K * V_BYTES..], K * O);
+            for (i, v_i) in v.iter().enumerate() {
+                record(&format!("v_{}", i), encode(v_i));
+            }
+
+            // Row a of M_i is v_i^T L_a
+            let m_matrices: Vec<Mat> = v
+                .iter()
+                .map(|v_i| l.iter().map(|l_a| mul_vec(&transpose(l_a), v_i)).collect())
+                .collect();
+            for (i, m_i) in m_matrices.iter().enumerate() {
+                record(&format!("M_{}", i), encode(&m_i.concat()));
+            }
+
+            // Columns of A and y in GF(16)[z]/f(z)
+            let mut a = vec![ExtensionElement::zero(); K * O];
+            let mut ell = 0;
+            for i in 0..K {
+                for j in (i..K).rev() {
+                    for col in 0..O {
+                        let m_j = transpose(&m_matrices[j])[col].clone();
+                        a[i * O + col] += element(m_j).mul_by_z_pow(ell);
+                        if i != j {
+                            let m_i = transpose(&m_matrices[i])[col].clone();
+                            a[j * O + col] += element(m_i).mul_by_z_pow(ell);
+                        }
+                    }
+                    ell += 1;
+                }
+            }
+            let y = element(t.clone()) - whipped_sum(&p1, &v);
+
+            let a_rows: Mat = (0..M)
+                .map(|row| a.iter().map(|column| column.0[row]).collect())
+                .collect();
+            record("A", encode(&a_rows.concat()));
+            record("y", encode(&y.0 .0));
+
+            // SampleSolution: x = random + a solution of A x' = y - A * random with the free
+            // variables set to zero, if A has full rank
+            let rhs: Vec<Gf16> = a_rows
+                .iter()
+                .zip(y.0.iter())
+                .map(|(row, y)| *y - dot(row, &random))
+                .collect();
+            let augmented: Vec<Gf16> = a_rows
+                .iter()
+                .zip(&rhs)
+                .flat_map(|(row, rhs)| row.iter().chain([rhs]).copied())
+                .collect();
+            let system = DynMatrix::from_elements(M, K * O + 1, augmented).unwrap();
+            let (reduced, pivots) = system.reduced_echelon_form();
+            if pivots.iter().filter(|&&column| column < K * O).count() < M {
+                continue;
+            }
+            let mut x = random;
+            for (row, &column) in pivots.iter().enumerate() {
+                x[column] += reduced[(row, K * O)];
+            }
+            solution = Some((v, x));
+            break;
+        }
+        let (v, x) = solution.expect("No solution for any ctr");
+        record("x", encode(&x));
+
+        // s_i = (v_i + O x_i, x_i)
+        let s: Vec<Vec<Gf16>> = v
+            .iter()
+            .zip(x.chunks(O))
+            .map(|(v_i, x_i)| {
+                let o_x = mul_vec(&o, x_i);
+                v_i.iter().zip(o_x).map(|(v, o_x)| *v + o_x).chain(x_i.to_vec()).collect()
+            })
+            .collect();
+        record("s", encode(&s.concat()));
+
+        // Verify (Algorithm 9) with P*_a = [[P1_a, P2_a], [0, P3_a]]
+        let p_star: Vec<Mat> = (0..M)
+            .map(|a| {
+                let mut p = vec![vec![Gf16::ZERO; N]; N];
+                for i in 0..V {
+                    p[i][..V].copy_from_slice(&p1[a][i]);
+                    p[i][V..].copy_from_slice(&p2[a][i]);
+                }
+                for i in 0..O {
+                    p[V + i][V..].copy_from_slice(&p3[a][i]);
+                }
+                p
+            })
+            .collect();
+        record("s", encode(&s.concat()));
+        record("t", encode(&t));
+        record("y", encode(&(ExtensionElement::zero() - whipped_sum(&p_star, &s)).0 .0));
+
+        trace
+    }
+}
+
+#[test]
+fn test_trace_vectors() {
+    let path = vector_path();
+    let text =
+        fs::read_to_string(&path).unwrap_or_else(|error| panic!("{}: {}", path.display(), error));
+    let expected = Trace::parse(&text).unwrap();
+
+    let traced = traced_operations(
+        expected.get("sk_seed").unwrap(),
+        expected.get("msg").unwrap(),
+        expected.get("r").unwrap(),
+    );
+    assert!(traced.valid);
+    if let Err(difference) = traced.trace.compare(&expected) {
+        panic!("{}: {}", path.display(), difference);
+    }
+
+    // The vectors belong to the first KAT record
+    let kat = first_kat_record();
+    assert_eq!(expected.get("sk_seed"), kat.sk.as_deref());
+    assert_eq!(Some(traced.public_key), kat.pk);
+    assert_eq!(Some([traced.signature, kat.msg].concat()), kat.sm);
+}
+
+#[test]
+fn test_reference_trace() {
+    let path = vector_path();
+    let expected = Trace::parse(&fs::read_to_string(&path).unwrap()).unwrap();
+
+    let reference = reference::trace(
+        expected.get("sk_seed").unwrap(),
+        expected.get("msg").unwrap(),
+        expected.get("r").unwrap(),
+    );
+    if let Err(difference) = reference.compare(&expected) {
+        panic!("{} against the reference: {}", path.display(), difference);
+    }
+}
+
+#[test]
+fn test_trace_names() {
+    let text = fs::read_to_string(vector_path()).unwrap();
+    let expected = Trace::parse(&text).unwrap();
+    let names: Vec<&str> = expected
+        .entries
+        .iter()
+        .map(|(name, _)| name.split('.').next().unwrap())
+        .collect();
+
+    // Keygen, expand_sk and the first signing attempt
+    let mut prefix = vec!["sk_seed", "msg", "r", "pk_seed", "O", "P3", "O", "P1", "L"];
+    prefix.extend(["salt", "t", "ctr", "v_0"]);
+    assert_eq!(names[..prefix.len()], prefix[..]);
+    assert_eq!(names[names.len() - 5..], ["x", "s", "s", "t", "y"]);
+    assert_eq!(expected.get("pk_seed").unwrap().len(), 16);
+    assert_eq!(expected.get("sk_seed").unwrap().len(), CSK_BYTES);
+
+    // Verification recomputes the signature vector and t of the signer
+    let signer_s = names.iter().position(|name| *name == "s").unwrap();
+    assert_eq!(
+        expected.entries[signer_s].1,
+        expected.entries[signer_s + 1].1
+    );
+    assert_eq!(
+        expected.get("t"),
+        Some(&expected.entries[names.len() - 2].1[..])
+    );
+}
+
+// Writes the vectors of the compiled parameter set:
+//   cargo test --test trace --no-default-features --features mayoN,trace -- --ignored
+#[test]
+#[ignore]
+fn generate_trace_vectors() {
+    let kat = first_kat_record();
+
+    // The randomness keygen and sign draw for the first KAT record
+    let mut seed = kat.seed;
+    safe_random_bytes_init(&mut seed, &[0u8; KAT_SEED_BYTES], 256);
+    let mut sk_seed = [0u8; SK_SEED_BYTES];
+    safe_random_bytes(&mut sk_seed, SK_SEED_BYTES as u64);
+    let mut r = [0u8; R_BYTES];
+    safe_random_bytes(&mut r, R_BYTES as u64);
+
+    let traced = traced_operations(&sk_seed, &kat.msg, &r);
+    assert!(traced.valid);
+    assert_eq!(Some(traced.public_key.clone()), kat.pk);
+    assert_eq!(traced.public_key.len(), CPK_BYTES);
+    if let Err(difference) = traced.trace.compare(&reference::trace(&sk_seed, &kat.msg, &r)) {
+        panic!("The implementation differs from the reference: {}", difference);
+    }
+
+    let header = format!(
+        "# {} intermediate values of keygen, sign and verify for count = 0 of the NIST KAT\n\
+         # Checked against the independent reference in tests/trace.rs and the NIST KAT\n\
+         # Regenerate with: cargo test --test trace --no-default-features --features {},trace -- --ignored\n",
+        VERSION,
+        VERSION.replace("_", "").to_lowercase()
+    );
+    let path = vector_path();
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, header + &traced.trace.to_text()).unwrap();
+}
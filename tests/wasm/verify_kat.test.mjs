@@ -0,0 +1,93 @@
+// Verifies the signatures of the KAT file with the WebAssembly build (src/wasm.rs) in Node:
+//
+//   wasm-pack build --target nodejs --no-default-features --features wasm,mayo1
+//   node --test tests/wasm/
+//
+// MAYO_WASM_PKG can point to a package built elsewhere (default: pkg/ in the repository root).
+import assert from 'node:assert/strict';
+import fs from 'node:fs';
+import { createRequire } from 'node:module';
+import path from 'node:path';
+import test from 'node:test';
+import { fileURLToPath } from 'node:url';
+
+const root = path.resolve(path.dirname(fileURLToPath(import.meta.url)), '..', '..');
+const require = createRequire(import.meta.url);
+const mayo = require(path.resolve(process.env.MAYO_WASM_PKG ?? path.join(root, 'pkg')));
+
+// Entries of the KAT file of the compiled parameter set, e.g. PQCsignKAT_24_MAYO_1.txt
+function readKat() {
+  const dir = path.join(root, 'src', 'genkat', 'Results MAYO');
+  const file = fs.readdirSync(dir).find((name) => name.endsWith(`_${mayo.version()}.txt`));
+  assert.ok(file, `No KAT file for ${mayo.version()}`);
+
+  return fs
+    .readFileSync(path.join(dir, file), 'utf8')
+    .split(/\n\s*\n/)
+    .filter((entry) => entry.includes('sm = '))
+    .map((entry) => {
+      const fields = {};
+      for (const line of entry.trim().split('\n')) {
+        const [name, value] = line.split(' = ');
+        fields[name] = value;
+      }
+      return {
+        pk: Buffer.from(fields.pk, 'hex'),
+        sk: Buffer.from(fields.sk, 'hex'),
+        sm: Buffer.from(fields.sm, 'hex'),
+      };
+    });
+}
+
+const kat = readKat();
+const sigBytes = mayo.signatureBytes();
+
+test('verifies all KAT signatures', () => {
+  assert.equal(kat.length, 100);
+  for (const { pk, sm } of kat) {
+    const signature = sm.subarray(0, sigBytes);
+    const message = sm.subarray(sigBytes);
+    assert.equal(mayo.verify(pk, message, signature), true);
+  }
+});
+
+test('rejects modified KAT signatures', () => {
+  const { pk, sm } = kat[0];
+  const signature = Buffer.from(sm.subarray(0, sigBytes));
+  const message = Buffer.from(sm.subarray(sigBytes));
+
+  message[0] ^= 1;
+  assert.equal(mayo.verify(pk, message, signature), false);
+  message[0] ^= 1;
+
+  signature[sigBytes - 1] ^= 1;
+  assert.equal(mayo.verify(pk, message, signature), false);
+  assert.equal(mayo.verify(pk, message, signature.subarray(1)), false);
+  assert.equal(mayo.verify(kat[1].pk, message, sm.subarray(0, sigBytes)), false);
+  assert.throws(() => mayo.verify(pk.subarray(1), message, signature));
+});
+
+test('derives the KAT public keys', () => {
+  for (const { pk, sk } of kat.slice(0, 5)) {
+    assert.deepEqual(Buffer.from(mayo.publicKey(sk)), pk);
+    assert.deepEqual(Buffer.from(mayo.keygen(sk).publicKey), pk);
+  }
+});
+
+test('signs with caller randomness', () => {
+  const { publicKey, secretKey } = mayo.keygen(crypto.getRandomValues(new Uint8Array(mayo.keygenRandomnessBytes())));
+  const message = new TextEncoder().encode('signed in WebAssembly');
+  const randomness = crypto.getRandomValues(new Uint8Array(mayo.signRandomnessBytes()));
+
+  const signature = mayo.sign(secretKey, message, randomness);
+  assert.equal(signature.length, sigBytes);
+  assert.equal(mayo.verify(publicKey, message, signature), true);
+  assert.deepEqual(mayo.sign(secretKey, message, randomness), signature);
+
+  const context = new TextEncoder().encode('context');
+  const contextSignature = mayo.sign(secretKey, message, randomness, context);
+  assert.equal(mayo.verify(publicKey, message, contextSignature, context), true);
+  assert.equal(mayo.verify(publicKey, message, contextSignature), false);
+
+  assert.throws(() => mayo.sign(secretKey, message, randomness.subarray(1)));
+});